@@ -1,4 +1,7 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use swtchr::logging::LogArgs;
+use swtchr::sway::SwayWindowId;
 
 /// A Gnome-style window switcher for the Sway window manager.
 ///
@@ -9,7 +12,71 @@ use clap::Parser;
 #[derive(Parser, Clone)]
 #[command(name = "swtchr", author, version, about)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<ClientCommand>,
+
+    /// Only switch between the windows of the focused app.
+    #[arg(long)]
+    pub app: bool,
+
+    /// Switch between workspaces instead of windows.
+    #[arg(long, conflicts_with = "app")]
+    pub workspaces: bool,
+
+    /// Switch between outputs (monitors) instead of windows.
+    #[arg(long, conflicts_with_all = ["app", "workspaces"])]
+    pub outputs: bool,
+
+    /// Open a view defined in the config file.
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["app", "workspaces", "outputs"])]
+    pub view: Option<String>,
+
     /// Skip checking that the client is running in a Sway session.
     #[arg(long)]
     pub no_check: bool,
+
+    #[command(flatten)]
+    pub log: LogArgs,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ClientCommand {
+    /// Pin a window to the front of the window switcher.
+    ///
+    /// Pinned windows are shown first, in the order they were pinned, until they're unpinned or
+    /// closed.
+    Pin {
+        /// The Sway `con_id` of the window to pin. Defaults to the focused window.
+        con_id: Option<SwayWindowId>,
+    },
+
+    /// Unpin a window.
+    Unpin {
+        /// The Sway `con_id` of the window to unpin. Defaults to the focused window.
+        con_id: Option<SwayWindowId>,
+    },
+
+    /// Put windows in numbered slots and jump straight to them.
+    Slot {
+        #[command(subcommand)]
+        command: SlotCommand,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum SlotCommand {
+    /// Put a window in a slot, replacing the window that was there.
+    Set {
+        /// The number of the slot.
+        slot: u32,
+
+        /// The Sway `con_id` of the window. Defaults to the focused window.
+        con_id: Option<SwayWindowId>,
+    },
+
+    /// Switch to the window in a slot.
+    Go {
+        /// The number of the slot.
+        slot: u32,
+    },
 }
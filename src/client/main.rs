@@ -3,38 +3,87 @@ mod cli;
 use std::os::unix::net::UnixDatagram;
 
 use clap::Parser;
-use eyre::Context;
+use eyre::{bail, eyre, Context};
 use swtchr::ipc::{sock_path, Command};
-use swtchr::sway;
+use swtchr::{logging, sway};
 
-use cli::Cli;
+use cli::{Cli, ClientCommand, SlotCommand};
+
+fn send_msg(command: Command) -> eyre::Result<()> {
+    let msg = command.msg();
+
+    // The daemon would only read part of a message this long.
+    if msg.len() > Command::BUF_LEN {
+        bail!("The command is too long to send to the swtchr daemon. Is the view name too long?");
+    }
 
-fn send_msg() -> eyre::Result<()> {
     let socket = UnixDatagram::unbound()?;
     socket
         .connect(sock_path())
         .wrap_err("Could not connect to swtchrd socket. Is the daemon running?")?;
 
+    tracing::debug!(?command, "Sending command to the swtchr daemon.");
+
     socket
-        .send(Command::Show.msg())
+        .send(&msg)
         .wrap_err("Failed sending a message to the swtchrd socket.")?;
 
     Ok(())
 }
 
+// Return the given window, or the focused window if none was given.
+fn window_or_focused(con_id: Option<sway::SwayWindowId>) -> eyre::Result<sway::SwayWindowId> {
+    match con_id {
+        Some(id) => Ok(id),
+        None => sway::focused_window()?.ok_or(eyre!("There is no focused window.")),
+    }
+}
+
 fn main() -> eyre::Result<()> {
     color_eyre::install()?;
 
     let args = Cli::parse();
 
+    logging::init(&args.log, "swtchr")?;
+
     if !args.no_check {
         sway::check_is_sway_session()?;
     }
 
-    if let Err(err) = send_msg() {
+    // These talk to Sway directly, so they work without opening the window switcher.
+    match args.command {
+        Some(ClientCommand::Pin { con_id }) => return sway::pin_window(window_or_focused(con_id)?),
+        Some(ClientCommand::Unpin { con_id }) => {
+            return sway::unpin_window(window_or_focused(con_id)?)
+        }
+        Some(ClientCommand::Slot {
+            command: SlotCommand::Set { slot, con_id },
+        }) => return sway::set_slot(slot, window_or_focused(con_id)?),
+        Some(ClientCommand::Slot {
+            command: SlotCommand::Go { slot },
+        }) => return sway::go_to_slot(slot),
+        None => {}
+    }
+
+    let command = if let Some(name) = args.view {
+        Command::ShowView(name)
+    } else if args.app {
+        Command::ShowApp
+    } else if args.workspaces {
+        Command::ShowWorkspaces
+    } else if args.outputs {
+        Command::ShowOutputs
+    } else {
+        Command::Show
+    };
+
+    if let Err(err) = send_msg(command) {
         // We weren't able to message the swtchrd socket to open the window switcher, so it can't
         // switch the Sway binding mode back to `default` for us. To avoid locking the user into
         // the `swtchr` binding mode, we should change the binding mode back to `default` here.
+        tracing::warn!(
+            "Could not reach the swtchr daemon. Switching Sway back to the default binding mode."
+        );
         sway::switch_mode(sway::SwayMode::Default)?;
 
         return Err(err);
@@ -1,5 +1,7 @@
 use clap::Parser;
 
+use swtchr::logging::LogArgs;
+
 /// A Gnome-style window switcher for the Sway window manager.
 ///
 /// This is the command to start the swtchr daemon. You should run this command when you start your
@@ -11,7 +13,14 @@ pub struct Cli {
     #[arg(short, long, value_name = "PATH")]
     pub config: Option<String>,
 
+    /// Check the config file for errors and exit.
+    #[arg(long)]
+    pub check_config: bool,
+
     /// Skip checking that the daemon is running in a Sway session.
     #[arg(long)]
     pub no_check: bool,
+
+    #[command(flatten)]
+    pub log: LogArgs,
 }
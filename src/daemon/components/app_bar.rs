@@ -1,8 +1,18 @@
 use glib::Object;
 use gtk::glib::{self, clone};
 use gtk::prelude::*;
+use gtk::subclass::prelude::*;
 
 use super::app_button::AppButton;
+use super::launch_button::LaunchButton;
+use crate::search::{fuzzy_match, highlight};
+use swtchr::sway::{search_desktop_entries, SwayWindowId};
+
+// The most apps to offer to launch when no windows match the search query.
+const MAX_LAUNCH_RESULTS: usize = 8;
+
+// How many buttons can be selected with the number keys.
+const MAX_INDEX: usize = 9;
 
 glib::wrapper! {
     pub struct AppBar(ObjectSubclass<imp::AppBar>)
@@ -11,28 +21,223 @@ glib::wrapper! {
 }
 
 impl AppBar {
-    pub fn new(app_buttons: &[AppButton]) -> Self {
+    // The button at the `initial` index is selected first, and again whenever the search query is
+    // cleared.
+    pub fn new(app_buttons: &[AppButton], initial: usize) -> Self {
         let obj: Self = Object::builder().build();
 
         for button in app_buttons.iter() {
             obj.append(button);
         }
 
-        // Initially focus the previous window instead of the current app so that the first call to
-        // `swtchr next` switches to the previous window.
-        if let Some(prev_app) = app_buttons.get(1) {
-            obj.set_focus_child(Some(prev_app));
+        if let Some(initial_app) = app_buttons.get(initial) {
+            obj.set_focus_child(Some(initial_app));
         }
 
         for app_button in app_buttons.iter() {
             app_button.connect_has_focus_notify(clone!(@weak obj => move |button| {
-                obj.set_current_title(button.window_title());
-                obj.set_window_id(button.window_id());
+                if button.has_focus() {
+                    obj.set_current(Some(button));
+                }
             }));
         }
 
+        obj.imp().buttons.replace(app_buttons.to_vec());
+        obj.imp().initial.set(initial);
+
         obj
     }
+
+    // Update the title and window ID to match the given button, highlighting the characters in the
+    // title that match the search query.
+    fn set_current(&self, button: Option<&AppButton>) {
+        self.set_desktop_file_id("");
+
+        let Some(button) = button else {
+            self.set_current_title("");
+            self.set_window_id(SwayWindowId::default());
+            self.set_scratchpad(false);
+            self.set_workspace_name("");
+            self.set_output_name("");
+            return;
+        };
+
+        let matched_indices = button
+            .search(&self.imp().query.borrow())
+            .unwrap_or_default();
+
+        self.set_current_title(highlight(&button.window_title(), &matched_indices));
+        self.set_window_id(button.window_id());
+        self.set_scratchpad(button.scratchpad());
+        self.set_workspace_name(button.workspace_name());
+        self.set_output_name(button.output_name());
+    }
+
+    // A row of apps to launch, like the user's favorite apps.
+    pub fn new_launchers(launch_buttons: &[LaunchButton]) -> Self {
+        let obj: Self = Object::builder().build();
+
+        obj.add_launch_buttons(launch_buttons);
+
+        obj
+    }
+
+    fn add_launch_buttons(&self, launch_buttons: &[LaunchButton]) {
+        for button in launch_buttons.iter() {
+            self.append(button);
+
+            button.connect_has_focus_notify(clone!(@weak self as obj => move |button| {
+                if button.has_focus() {
+                    obj.set_current_app(button);
+                }
+            }));
+        }
+
+        self.imp().launch_buttons.replace(launch_buttons.to_vec());
+    }
+
+    // Like `set_current`, but for an app to launch rather than a window. If the app is already
+    // running, switch to its window instead.
+    fn set_current_app(&self, button: &LaunchButton) {
+        let app_name = button.app_name();
+        let matched_indices =
+            fuzzy_match(&self.imp().query.borrow(), &app_name).unwrap_or_default();
+        let window_id = button.window_id();
+
+        self.set_current_title(highlight(&app_name, &matched_indices));
+        self.set_window_id(window_id);
+        self.set_scratchpad(button.scratchpad());
+        self.set_workspace_name("");
+        self.set_output_name("");
+
+        if window_id.is_null() {
+            self.set_desktop_file_id(button.desktop_file_id());
+        } else {
+            self.set_desktop_file_id("");
+        }
+    }
+
+    // Offer to launch the installed apps that match the search query.
+    fn show_launch_buttons(&self, query: &str) {
+        for button in self.imp().launch_buttons.take() {
+            self.remove(&button);
+        }
+
+        if query.is_empty() {
+            return;
+        }
+
+        let launch_buttons = search_desktop_entries(query)
+            .filter(|app_info| app_info.should_show())
+            .take(MAX_LAUNCH_RESULTS)
+            .map(|app_info| LaunchButton::new(&app_info))
+            .collect::<Vec<_>>();
+
+        self.add_launch_buttons(&launch_buttons);
+
+        if let Some(button) = launch_buttons.first() {
+            button.grab_focus();
+            self.set_current_app(button);
+        }
+    }
+
+    // Select the button for the given window, if there is one.
+    pub fn select_window(&self, window_id: SwayWindowId) {
+        let buttons = self.imp().buttons.borrow();
+
+        if let Some(button) = buttons
+            .iter()
+            .find(|button| button.window_id() == window_id)
+        {
+            button.grab_focus();
+            self.set_current(Some(button));
+        }
+    }
+
+    // Label the visible buttons with the number keys that select them, if this app bar is
+    // numbered.
+    pub fn set_numbered(&self, numbered: bool) {
+        self.imp().numbered.set(numbered);
+        self.update_index_labels();
+    }
+
+    fn update_index_labels(&self) {
+        let numbered = self.imp().numbered.get();
+        let mut index = 0;
+
+        for button in self.imp().buttons.borrow().iter() {
+            if numbered && button.is_visible() && index < MAX_INDEX {
+                index += 1;
+                button.set_index(Some(index));
+            } else {
+                button.set_index(None);
+            }
+        }
+    }
+
+    // Select the visible button with the given number, counting from 1. Returns `false` if there
+    // is no such button.
+    pub fn select_index(&self, index: usize) -> bool {
+        let buttons = self.imp().buttons.borrow();
+
+        let Some(button) = index.checked_sub(1).and_then(|index| {
+            buttons
+                .iter()
+                .filter(|button| button.is_visible())
+                .nth(index)
+        }) else {
+            return false;
+        };
+
+        button.grab_focus();
+        self.set_current(Some(button));
+
+        true
+    }
+
+    // Show whether the window is pinned on its button.
+    pub fn set_pinned(&self, window_id: SwayWindowId, pinned: bool) {
+        for button in self.imp().buttons.borrow().iter() {
+            if button.window_id() == window_id {
+                button.set_pinned(pinned);
+            }
+        }
+    }
+
+    // Only show the windows that match the search query, keeping them in the same order, and
+    // select the first one.
+    pub fn filter(&self, query: &str) {
+        self.imp().query.replace(query.to_owned());
+
+        let buttons = self.imp().buttons.borrow();
+
+        for button in buttons.iter() {
+            button.set_visible(button.search(query).is_some());
+        }
+
+        self.update_index_labels();
+
+        // With no search query, select the same window as when the window switcher was first
+        // opened.
+        let selected = if query.is_empty() {
+            buttons.get(self.imp().initial.get()).or(buttons.first())
+        } else {
+            buttons.iter().find(|button| button.is_visible())
+        };
+
+        if let Some(button) = selected {
+            button.grab_focus();
+        }
+
+        self.set_current(selected);
+
+        // If no windows match, fall back to offering to launch apps instead.
+        if selected.is_none() {
+            self.show_launch_buttons(query);
+        } else {
+            self.show_launch_buttons("");
+        }
+    }
 }
 
 mod imp {
@@ -45,15 +250,36 @@ mod imp {
     use gtk::subclass::prelude::*;
     use gtk::{Align, Orientation};
 
+    use super::{AppButton, LaunchButton};
     use swtchr::sway::SwayWindowId;
 
     #[derive(Debug, Default, Properties)]
     #[properties(wrapper_type = super::AppBar)]
     pub struct AppBar {
+        // This is Pango markup.
         #[property(get, set)]
         current_title: RefCell<String>,
         #[property(get, set)]
         window_id: Cell<SwayWindowId>,
+        // Whether the selected window is hidden in the scratchpad.
+        #[property(get, set)]
+        scratchpad: Cell<bool>,
+        // The desktop entry of the app to launch, if the user selected an app instead of a window.
+        #[property(get, set)]
+        desktop_file_id: RefCell<String>,
+        // The workspace to switch to, if the user selected a workspace instead of a window.
+        #[property(get, set)]
+        workspace_name: RefCell<String>,
+        // The output to focus, if the user selected an output instead of a window.
+        #[property(get, set)]
+        output_name: RefCell<String>,
+        pub(super) buttons: RefCell<Vec<AppButton>>,
+        // The index of the button to select when nothing has been searched for.
+        pub(super) initial: Cell<usize>,
+        // Whether the buttons are labeled with the number keys that select them.
+        pub(super) numbered: Cell<bool>,
+        pub(super) launch_buttons: RefCell<Vec<LaunchButton>>,
+        pub(super) query: RefCell<String>,
     }
 
     #[glib::object_subclass]
@@ -1,8 +1,18 @@
 use glib::Object;
 use gtk::glib;
+use gtk::pango::EllipsizeMode;
 use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::{Align, Label, Orientation};
 
-use swtchr::sway::Window;
+use crate::search::fuzzy_match;
+use swtchr::sway::{Output, Window, Workspace};
+
+// The most window icons to show on a workspace tile.
+const MAX_WORKSPACE_ICONS: usize = 6;
+
+// The name of the standard icon for a monitor.
+const GTK_DISPLAY_ICON: &str = "video-display";
 
 glib::wrapper! {
     pub struct AppButton(ObjectSubclass<imp::AppButton>)
@@ -10,21 +20,234 @@ glib::wrapper! {
         @implements gtk::Accessible, gtk::Actionable, gtk::Buildable, gtk::ConstraintTarget;
 }
 
+fn window_icon(window: &Window, size: i32) -> gtk::Image {
+    let image = window
+        .icon_locator
+        .icon()
+        .expect("Failed finding an icon for the window.");
+
+    image.set_pixel_size(size);
+
+    image
+}
+
 impl AppButton {
     pub fn new(window: &Window) -> Self {
-        let image = window
-            .icon_locator
-            .icon()
-            .expect("Failed finding an icon for the window.");
+        Self::with_child(window, &window_icon(window, 80), 1)
+    }
+
+    // A button for all the windows of an app, with a badge showing how many windows there are.
+    // The windows should be sorted from most recently used to least recently used.
+    pub fn new_group(windows: &[Window]) -> Self {
+        let window = windows
+            .first()
+            .expect("Cannot create a button for an empty group of windows.");
+
+        let badge = gtk::Overlay::builder()
+            .child(&window_icon(window, 80))
+            .build();
+
+        if windows.len() > 1 {
+            let count_label = Label::builder()
+                .label(windows.len().to_string())
+                .css_classes(["window-count"])
+                .halign(Align::End)
+                .valign(Align::Start)
+                .build();
+
+            badge.add_overlay(&count_label);
+        }
+
+        Self::with_child(window, &badge, windows.len() as u32)
+    }
+
+    // A smaller button that shows the window's title, for listing the windows of an app.
+    pub fn new_titled(window: &Window) -> Self {
+        let title_label = Label::builder()
+            .label(window.title.as_str())
+            .ellipsize(EllipsizeMode::End)
+            .max_width_chars(20)
+            .build();
+
+        let child = gtk::Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(5)
+            .build();
+
+        child.append(&window_icon(window, 48));
+        child.append(&title_label);
+
+        Self::with_child(window, &child, 1)
+    }
+
+    // A tile for a workspace, showing the icons of the windows on it.
+    pub fn new_workspace(workspace: &Workspace) -> Self {
+        let icons = gtk::Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(5)
+            .halign(Align::Center)
+            .css_classes(["workspace-icons"])
+            .build();
+
+        for window in workspace.windows.iter().take(MAX_WORKSPACE_ICONS) {
+            icons.append(&window_icon(window, 40));
+        }
+
+        let name_label = Label::builder()
+            .label(workspace.name.as_str())
+            .css_classes(["workspace-name"])
+            .build();
+
+        let child = gtk::Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(5)
+            .build();
+
+        child.append(&icons);
+        child.append(&name_label);
+
+        let button: Self = Object::builder()
+            .property("css-classes", ["app-icon", "workspace"].to_value())
+            .property("window-title", workspace.name.clone())
+            .property("window-count", workspace.windows.len() as u32)
+            .property("workspace-name", workspace.name.clone())
+            .build();
+
+        button.set_content(&child);
+
+        // Let the user find a workspace by searching for the windows on it.
+        button.imp().search_terms.replace(
+            workspace
+                .windows
+                .iter()
+                .flat_map(|window| {
+                    [window.title.clone()]
+                        .into_iter()
+                        .chain(window.app_id.clone())
+                })
+                .collect(),
+        );
+
+        button
+    }
+
+    // A tile for an output, showing its name and the workspace that's visible on it.
+    pub fn new_output(output: &Output) -> Self {
+        let image = gtk::Image::from_icon_name(GTK_DISPLAY_ICON);
+        image.set_pixel_size(64);
+
+        let name_label = Label::builder()
+            .label(output.name.as_str())
+            .css_classes(["output-name"])
+            .build();
 
-        image.set_pixel_size(80);
+        let child = gtk::Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(5)
+            .build();
 
-        Object::builder()
+        child.append(&image);
+        child.append(&name_label);
+
+        // Like "Dell Inc. DELL U2720Q — 3".
+        let description = format!(
+            "{} {} — {}",
+            output.make,
+            output.model,
+            output
+                .current_workspace
+                .as_deref()
+                .unwrap_or("No workspace"),
+        );
+
+        let button: Self = Object::builder()
+            .property("css-classes", ["app-icon", "output"].to_value())
+            .property("window-title", description)
+            .property("output-name", output.name.clone())
+            .build();
+
+        button.set_content(&child);
+
+        button.imp().search_terms.replace(vec![output.name.clone()]);
+
+        button
+    }
+
+    fn with_child(window: &Window, child: &impl IsA<gtk::Widget>, window_count: u32) -> Self {
+        let button: Self = Object::builder()
             .property("css-classes", ["app-icon"].to_value())
-            .property("child", image)
             .property("window-id", window.id)
             .property("window-title", window.title.clone())
-            .build()
+            .property("window-count", window_count)
+            .property("scratchpad", window.scratchpad)
+            .property("pinned", window.pin.is_some())
+            .build();
+
+        button.set_content(child);
+
+        if window.scratchpad {
+            button.add_css_class("scratchpad");
+        }
+
+        button.connect_pinned_notify(|button| {
+            if button.pinned() {
+                button.add_css_class("pinned");
+            } else {
+                button.remove_css_class("pinned");
+            }
+        });
+
+        button.notify_pinned();
+
+        // Looking up the window's desktop entry is slow, so wait until the user searches.
+        button
+            .imp()
+            .icon_locator
+            .replace(Some(window.icon_locator.clone()));
+
+        button
+    }
+
+    // Show the child with a label in the corner for the number key that selects this button.
+    fn set_content(&self, child: &impl IsA<gtk::Widget>) {
+        let index_label = Label::builder()
+            .css_classes(["window-index"])
+            .halign(Align::Start)
+            .valign(Align::Start)
+            .visible(false)
+            .build();
+
+        let content = gtk::Overlay::builder().child(child).build();
+        content.add_overlay(&index_label);
+
+        self.set_child(Some(&content));
+        self.imp().index_label.replace(Some(index_label));
+    }
+
+    // Show which number key selects this button, or hide the label if none does.
+    pub fn set_index(&self, index: Option<usize>) {
+        if let Some(index_label) = self.imp().index_label.borrow().as_ref() {
+            index_label.set_label(&index.map(|index| index.to_string()).unwrap_or_default());
+            index_label.set_visible(index.is_some());
+        }
+    }
+
+    // Match the query against this window, returning the indices of the characters in the window
+    // title that matched, or `None` if the window doesn't match. If the query only matches one of
+    // the other search terms, like the app ID, no characters in the title are matched.
+    pub fn search(&self, query: &str) -> Option<Vec<usize>> {
+        fuzzy_match(query, &self.window_title()).or_else(|| {
+            if let Some(icon_locator) = self.imp().icon_locator.take() {
+                self.imp().search_terms.replace(icon_locator.search_terms());
+            }
+
+            self.imp()
+                .search_terms
+                .borrow()
+                .iter()
+                .any(|term| fuzzy_match(query, term).is_some())
+                .then(Vec::new)
+        })
     }
 }
 
@@ -37,7 +260,7 @@ mod imp {
     use gtk::prelude::*;
     use gtk::subclass::prelude::*;
 
-    use swtchr::sway::{self, SwayWindowId};
+    use swtchr::sway::{IconLocator, SwayWindowId};
 
     #[derive(Debug, Default, Properties)]
     #[properties(wrapper_type = super::AppButton)]
@@ -46,6 +269,24 @@ mod imp {
         window_id: Cell<SwayWindowId>,
         #[property(get, set)]
         window_title: RefCell<String>,
+        // How many windows this button represents, when windows are grouped by app.
+        #[property(get, set)]
+        window_count: Cell<u32>,
+        // Whether the window is hidden in the scratchpad.
+        #[property(get, set)]
+        scratchpad: Cell<bool>,
+        #[property(get, set)]
+        pinned: Cell<bool>,
+        // The workspace to switch to, if this button is for a workspace instead of a window.
+        #[property(get, set)]
+        workspace_name: RefCell<String>,
+        // The output to focus, if this button is for an output instead of a window.
+        #[property(get, set)]
+        output_name: RefCell<String>,
+        pub(super) search_terms: RefCell<Vec<String>>,
+        // The window's search terms haven't been looked up yet while this is set.
+        pub(super) icon_locator: RefCell<Option<IconLocator>>,
+        pub(super) index_label: RefCell<Option<gtk::Label>>,
     }
 
     #[glib::object_subclass]
@@ -61,9 +302,26 @@ mod imp {
     impl WidgetImpl for AppButton {}
 
     impl ButtonImpl for AppButton {
+        // Clicking a window peeks at it, so that it doesn't change the window history until the
+        // window switcher is closed. Clicking an app with multiple windows shows its windows
+        // instead.
         fn clicked(&self) {
-            sway::switch_window(self.window_id.get())
-                .expect("Failed dispatching command to switching window focus on button click.");
+            let button = self.obj();
+
+            button.grab_focus();
+
+            let action = if !button.workspace_name().is_empty() || !button.output_name().is_empty()
+            {
+                "win.peek"
+            } else if button.window_count() > 1 {
+                "win.expand"
+            } else {
+                "win.peek"
+            };
+
+            button
+                .activate_action(action, None)
+                .expect("Failed activating a GTK action on button click.");
         }
     }
 }
@@ -0,0 +1,110 @@
+use glib::Object;
+use gtk::gio::DesktopAppInfo;
+use gtk::glib;
+use gtk::prelude::*;
+
+use swtchr::sway::{SwayWindowId, Window};
+
+// The name of the standard icon for apps that don't have an icon of their own.
+const GTK_DEFAULT_APP_ICON: &str = "application-x-executable";
+
+glib::wrapper! {
+    pub struct LaunchButton(ObjectSubclass<imp::LaunchButton>)
+        @extends gtk::Button, gtk::Widget,
+        @implements gtk::Accessible, gtk::Actionable, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl LaunchButton {
+    pub fn new(app_info: &DesktopAppInfo) -> Self {
+        Self::with_window(app_info, SwayWindowId::default(), 80)
+    }
+
+    // A favorite app, which switches to the app's most recently used window if it's running, or
+    // launches the app if it's not. Apps that aren't running are dimmed.
+    pub fn new_favorite(app_info: &DesktopAppInfo, window: Option<&Window>) -> Self {
+        let button = Self::with_window(
+            app_info,
+            window.map(|window| window.id).unwrap_or_default(),
+            48,
+        );
+
+        button.add_css_class("favorite");
+
+        match window {
+            Some(window) => button.set_scratchpad(window.scratchpad),
+            None => button.add_css_class("not-running"),
+        }
+
+        button
+    }
+
+    fn with_window(app_info: &DesktopAppInfo, window_id: SwayWindowId, icon_size: i32) -> Self {
+        let image = match app_info.icon() {
+            Some(icon) => gtk::Image::from_gicon(&icon),
+            None => gtk::Image::from_icon_name(GTK_DEFAULT_APP_ICON),
+        };
+
+        image.set_pixel_size(icon_size);
+
+        Object::builder()
+            .property("css-classes", ["app-icon", "launcher"].to_value())
+            .property("child", image)
+            .property(
+                "desktop-file-id",
+                app_info.id().map(String::from).unwrap_or_default(),
+            )
+            .property("app-name", app_info.name().to_string())
+            .property("window-id", window_id)
+            .build()
+    }
+}
+
+mod imp {
+    use std::cell::Cell;
+    use std::cell::RefCell;
+
+    use glib::Properties;
+    use gtk::glib;
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+
+    use swtchr::sway::SwayWindowId;
+
+    #[derive(Debug, Default, Properties)]
+    #[properties(wrapper_type = super::LaunchButton)]
+    pub struct LaunchButton {
+        #[property(get, set)]
+        desktop_file_id: RefCell<String>,
+        #[property(get, set)]
+        app_name: RefCell<String>,
+        // The window to switch to instead of launching the app, if the app is running.
+        #[property(get, set)]
+        window_id: Cell<SwayWindowId>,
+        // Whether that window is hidden in the scratchpad.
+        #[property(get, set)]
+        scratchpad: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LaunchButton {
+        const NAME: &'static str = "SwtchrLaunchButton";
+        type Type = super::LaunchButton;
+        type ParentType = gtk::Button;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for LaunchButton {}
+
+    impl WidgetImpl for LaunchButton {}
+
+    impl ButtonImpl for LaunchButton {
+        fn clicked(&self) {
+            let button = self.obj();
+
+            button.grab_focus();
+            button
+                .activate_action("win.select", None)
+                .expect("Failed activating the select action on button click.");
+        }
+    }
+}
@@ -1,5 +1,6 @@
 mod app_bar;
 mod app_button;
+mod launch_button;
 mod overlay;
 mod window;
 
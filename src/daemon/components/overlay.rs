@@ -1,11 +1,16 @@
+use std::cmp;
+
 use glib::Object;
-use gtk::glib;
+use gtk::glib::{self, clone};
 use gtk::prelude::*;
 use gtk::Label;
 
+use gtk::subclass::prelude::*;
+
 use super::app_bar::AppBar;
 use super::app_button::AppButton;
-use swtchr::sway::Window;
+use super::launch_button::LaunchButton;
+use swtchr::sway::{Output, SwayWindowId, Window, Workspace};
 
 glib::wrapper! {
     pub struct Overlay(ObjectSubclass<imp::Overlay>)
@@ -13,36 +18,303 @@ glib::wrapper! {
         @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
 }
 
+// Group windows by app, keeping both the groups and the windows within each group in the same order
+// as the windows.
+fn group_windows(windows: &[Window]) -> Vec<Vec<Window>> {
+    let mut groups: Vec<Vec<Window>> = Vec::new();
+
+    for window in windows {
+        let group = groups.iter_mut().find(|group| {
+            window.app_id.is_some()
+                && group.first().map(|first| &first.app_id) == Some(&window.app_id)
+        });
+
+        match group {
+            Some(group) => group.push(window.clone()),
+            None => groups.push(vec![window.clone()]),
+        }
+    }
+
+    groups
+}
+
 impl Overlay {
     pub fn new() -> Self {
         Object::builder().build()
     }
 
-    pub fn update_windows(&self, windows: &[Window]) {
+    // Keep the selected window and title in sync with the app bar.
+    fn bind_app_bar(&self, app_bar: &AppBar) {
+        app_bar
+            .bind_property("current-title", self, "current-title")
+            .sync_create()
+            .build();
+
+        app_bar
+            .bind_property("window-id", self, "window-id")
+            .sync_create()
+            .build();
+
+        app_bar
+            .bind_property("scratchpad", self, "scratchpad")
+            .sync_create()
+            .build();
+
+        app_bar
+            .bind_property("desktop-file-id", self, "desktop-file-id")
+            .sync_create()
+            .build();
+
+        app_bar
+            .bind_property("workspace-name", self, "workspace-name")
+            .sync_create()
+            .build();
+
+        app_bar
+            .bind_property("output-name", self, "output-name")
+            .sync_create()
+            .build();
+    }
+
+    // Windows are shown in the order they're given, but the most recently used window other than
+    // the focused one is selected first, no matter which order the windows are in. That way,
+    // selecting right away switches back to the previous window.
+    pub fn update_windows(&self, windows: &[Window], group_by_app: bool, focused: SwayWindowId) {
+        self.imp().focused.set(focused);
+
+        let (app_buttons, initial) = if group_by_app {
+            let groups = group_windows(windows);
+            let buttons = groups
+                .iter()
+                .map(|group| AppButton::new_group(group))
+                .collect::<Vec<_>>();
+            // Select the most recently used app other than the focused one.
+            let initial = groups
+                .iter()
+                .enumerate()
+                .filter(|(_, group)| group.iter().all(|window| window.id != focused))
+                .min_by_key(|(_, group)| {
+                    cmp::Reverse(group.iter().map(|window| window.last_used).max())
+                })
+                .map(|(index, _)| index);
+
+            self.imp().groups.replace(groups);
+
+            (buttons, initial)
+        } else {
+            self.imp().groups.replace(Vec::new());
+
+            let buttons = windows.iter().map(AppButton::new).collect::<Vec<_>>();
+            let initial = windows
+                .iter()
+                .enumerate()
+                .filter(|(_, window)| window.id != focused)
+                .min_by_key(|(_, window)| cmp::Reverse(window.last_used))
+                .map(|(index, _)| index);
+
+            (buttons, initial)
+        };
+
+        // If there's no other window, start from the top.
+        let initial = initial.unwrap_or(0);
+
+        self.set_buttons(&app_buttons, initial);
+    }
+
+    pub fn update_workspaces(&self, workspaces: &[Workspace]) {
+        self.imp().groups.replace(Vec::new());
+
+        // Workspaces are in most-recently-used order, so select the previous workspace first.
+        self.set_buttons(
+            &workspaces
+                .iter()
+                .map(AppButton::new_workspace)
+                .collect::<Vec<_>>(),
+            1,
+        );
+    }
+
+    pub fn update_outputs(&self, outputs: &[Output]) {
+        self.imp().groups.replace(Vec::new());
+
+        self.set_buttons(
+            &outputs
+                .iter()
+                .map(AppButton::new_output)
+                .collect::<Vec<_>>(),
+            1,
+        );
+    }
+
+    fn set_buttons(&self, app_buttons: &[AppButton], initial: usize) {
         // Remove all children.
         while let Some(child) = self.last_child() {
             self.remove(&child);
         }
 
-        let app_bar = AppBar::new(&windows.iter().map(AppButton::new).collect::<Vec<_>>());
+        self.imp().group_row.replace(None);
+        self.imp().favorites_row.replace(None);
+
+        let app_bar = AppBar::new(app_buttons, initial);
+        app_bar.set_numbered(true);
 
         let window_label = Label::builder()
             .name("window-title")
             .justify(gtk::Justification::Center)
+            .use_markup(true)
             .build();
 
-        app_bar
-            .bind_property("current-title", &window_label, "label")
-            .sync_create()
+        let search_label = Label::builder()
+            .name("search-query")
+            .justify(gtk::Justification::Center)
+            .visible(false)
             .build();
 
-        app_bar
-            .bind_property("window-id", self, "window-id")
+        self.bind_property("current-title", &window_label, "label")
             .sync_create()
             .build();
 
+        self.bind_app_bar(&app_bar);
+
+        // Filter the windows as the user types a search query.
+        self.connect_query_notify(clone!(@weak app_bar, @weak search_label => move |overlay| {
+            let query = overlay.query();
+
+            overlay.collapse_group();
+            app_bar.filter(&query);
+
+            search_label.set_label(&query);
+            search_label.set_visible(!query.is_empty());
+        }));
+
         self.append(&app_bar);
         self.append(&window_label);
+        self.append(&search_label);
+
+        self.imp().app_bar.replace(Some(app_bar));
+    }
+
+    // Show a row of the user's favorite apps below the windows.
+    pub fn set_favorites(&self, favorites: &[LaunchButton]) {
+        if let Some(row) = self.imp().favorites_row.take() {
+            self.remove(&row);
+        }
+
+        if favorites.is_empty() {
+            return;
+        }
+
+        let row = AppBar::new_launchers(favorites);
+        row.add_css_class("favorites");
+
+        if let Some(app_bar) = self.imp().app_bar.borrow().as_ref() {
+            self.insert_child_after(&row, Some(app_bar));
+        }
+
+        self.bind_app_bar(&row);
+
+        self.imp().favorites_row.replace(Some(row));
+    }
+
+    // Select the button in the main row with the given number, counting from 1. Returns `false`
+    // if there is no such button.
+    pub fn select_index(&self, index: usize) -> bool {
+        self.collapse_group();
+
+        self.imp()
+            .app_bar
+            .borrow()
+            .as_ref()
+            .map(|app_bar| app_bar.select_index(index))
+            .unwrap_or(false)
+    }
+
+    // Show whether the window is pinned on its buttons. Windows aren't reordered until the window
+    // switcher is opened again, so they don't jump around under the user.
+    pub fn set_pinned(&self, window_id: SwayWindowId, pinned: bool) {
+        let imp = self.imp();
+
+        for app_bar in [
+            imp.app_bar.borrow().as_ref(),
+            imp.group_row.borrow().as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            app_bar.set_pinned(window_id, pinned);
+        }
+    }
+
+    // Hide the windows of the expanded app and select the app again. Returns `false` if no app was
+    // expanded.
+    fn collapse_group(&self) -> bool {
+        let Some(group_row) = self.imp().group_row.take() else {
+            return false;
+        };
+
+        let group_id = self
+            .imp()
+            .groups
+            .borrow()
+            .iter()
+            .find(|group| group.iter().any(|window| window.id == self.window_id()))
+            .and_then(|group| group.first())
+            .map(|window| window.id);
+
+        self.remove(&group_row);
+
+        if let (Some(app_bar), Some(group_id)) = (self.imp().app_bar.borrow().as_ref(), group_id) {
+            app_bar.select_window(group_id);
+        }
+
+        true
+    }
+
+    // Show the windows of the selected app as a second row, or hide them if they're already
+    // showing. Returns `false` if windows aren't grouped by app.
+    pub fn toggle_group(&self) -> bool {
+        if self.collapse_group() {
+            return true;
+        }
+
+        let groups = self.imp().groups.borrow();
+
+        if groups.is_empty() {
+            return false;
+        }
+
+        let Some(group) = groups
+            .iter()
+            .find(|group| group.iter().any(|window| window.id == self.window_id()))
+        else {
+            return true;
+        };
+
+        // If this app has the focused window, select the window after it, so we don't select the
+        // window that's already focused.
+        let initial = group
+            .iter()
+            .position(|window| window.id == self.imp().focused.get())
+            .map(|index| (index + 1) % group.len())
+            .unwrap_or(0);
+
+        let buttons = group.iter().map(AppButton::new_titled).collect::<Vec<_>>();
+        let group_row = AppBar::new(&buttons, initial);
+        group_row.add_css_class("window-row");
+
+        if let Some(app_bar) = self.imp().app_bar.borrow().as_ref() {
+            self.insert_child_after(&group_row, Some(app_bar));
+        }
+
+        self.bind_app_bar(&group_row);
+
+        if let Some(button) = buttons.get(initial) {
+            button.grab_focus();
+        }
+
+        self.imp().group_row.replace(Some(group_row));
+
+        true
     }
 }
 
@@ -54,6 +326,7 @@ impl Default for Overlay {
 
 mod imp {
     use std::cell::Cell;
+    use std::cell::RefCell;
 
     use glib::Properties;
     use gtk::glib;
@@ -61,13 +334,36 @@ mod imp {
     use gtk::subclass::prelude::*;
     use gtk::{Align, Orientation};
 
-    use swtchr::sway::SwayWindowId;
+    use super::AppBar;
+    use swtchr::sway::{SwayWindowId, Window};
 
     #[derive(Debug, Default, Properties)]
     #[properties(wrapper_type = super::Overlay)]
     pub struct Overlay {
+        // This is Pango markup.
+        #[property(get, set)]
+        current_title: RefCell<String>,
         #[property(get, set)]
         window_id: Cell<SwayWindowId>,
+        #[property(get, set)]
+        scratchpad: Cell<bool>,
+        #[property(get, set)]
+        desktop_file_id: RefCell<String>,
+        #[property(get, set)]
+        workspace_name: RefCell<String>,
+        #[property(get, set)]
+        output_name: RefCell<String>,
+        #[property(get, set)]
+        query: RefCell<String>,
+        pub(super) app_bar: RefCell<Option<AppBar>>,
+        // The windows grouped by app, if windows are grouped by app.
+        pub(super) groups: RefCell<Vec<Vec<Window>>>,
+        // The windows of the app the user expanded, if any.
+        pub(super) group_row: RefCell<Option<AppBar>>,
+        // The user's favorite apps, if they have any.
+        pub(super) favorites_row: RefCell<Option<AppBar>>,
+        // The window that was focused when the window switcher opened.
+        pub(super) focused: Cell<SwayWindowId>,
     }
 
     #[glib::object_subclass]
@@ -1,11 +1,13 @@
 use glib::Object;
-use gtk::gio;
+use gtk::gio::{self, DesktopAppInfo};
 use gtk::glib;
 use gtk::prelude::*;
+use gtk::subclass::prelude::*;
 use gtk::Application;
 
+use super::launch_button::LaunchButton;
 use super::overlay::Overlay;
-use swtchr::sway;
+use swtchr::sway::{self, SwayWindowId};
 
 glib::wrapper! {
     pub struct Window(ObjectSubclass<imp::Window>)
@@ -22,35 +24,202 @@ impl Window {
             .build()
     }
 
-    pub fn update_windows(&self, windows: &[sway::Window]) {
+    pub fn update_windows(
+        &self,
+        windows: &[sway::Window],
+        group_by_app: bool,
+        focused: SwayWindowId,
+    ) {
         let overlay = Overlay::new();
+        overlay.update_windows(windows, group_by_app, focused);
 
+        self.set_overlay(&overlay, focused);
+    }
+
+    pub fn update_workspaces(
+        &self,
+        workspaces: &[sway::Workspace],
+        focused: SwayWindowId,
+        focused_workspace: &str,
+    ) {
+        let overlay = Overlay::new();
+        overlay.update_workspaces(workspaces);
+
+        self.set_overlay(&overlay, focused);
+        self.set_initial_workspace_name(focused_workspace);
+    }
+
+    pub fn update_outputs(
+        &self,
+        outputs: &[sway::Output],
+        focused: SwayWindowId,
+        focused_output: &str,
+    ) {
+        let overlay = Overlay::new();
+        overlay.update_outputs(outputs);
+
+        self.set_overlay(&overlay, focused);
+        self.set_initial_output_name(focused_output);
+    }
+
+    fn set_overlay(&self, overlay: &Overlay, focused: SwayWindowId) {
         overlay
             .bind_property("window-id", self, "window-id")
             .sync_create()
             .build();
 
-        overlay.update_windows(windows);
+        overlay
+            .bind_property("scratchpad", self, "scratchpad")
+            .sync_create()
+            .build();
 
-        self.set_child(Some(&overlay));
+        overlay
+            .bind_property("desktop-file-id", self, "desktop-file-id")
+            .sync_create()
+            .build();
+
+        overlay
+            .bind_property("workspace-name", self, "workspace-name")
+            .sync_create()
+            .build();
+
+        overlay
+            .bind_property("output-name", self, "output-name")
+            .sync_create()
+            .build();
+
+        // Remember which window was focused when the window switcher opened, so we can restore
+        // focus to it if the user peeks at other windows and then dismisses the window switcher.
+        self.set_initial_window_id(focused);
+        self.set_initial_workspace_name("");
+        self.set_initial_output_name("");
+        self.set_peeked(false);
+        self.imp().shown_scratchpad.take();
+
+        self.set_child(Some(overlay));
+    }
+
+    fn overlay(&self) -> Option<Overlay> {
+        self.child().and_downcast::<Overlay>()
+    }
+
+    // The text the user has typed to search for a window.
+    pub fn search_query(&self) -> String {
+        self.overlay()
+            .map(|overlay| overlay.query())
+            .unwrap_or_default()
+    }
+
+    pub fn set_search_query(&self, query: &str) {
+        if let Some(overlay) = self.overlay() {
+            overlay.set_query(query);
+        }
+    }
+
+    // Show a row of favorite apps, along with the most recently used window of each favorite app
+    // that's running.
+    pub fn set_favorites(&self, favorites: &[(DesktopAppInfo, Option<sway::Window>)]) {
+        let buttons = favorites
+            .iter()
+            .map(|(app_info, window)| LaunchButton::new_favorite(app_info, window.as_ref()))
+            .collect::<Vec<_>>();
+
+        if let Some(overlay) = self.overlay() {
+            overlay.set_favorites(&buttons);
+        }
+    }
+
+    // Select the window with the given number, counting from 1. Returns `false` if there is no
+    // such window.
+    pub fn select_index(&self, index: usize) -> bool {
+        self.overlay()
+            .map(|overlay| overlay.select_index(index))
+            .unwrap_or(false)
+    }
+
+    pub fn set_pinned(&self, window_id: SwayWindowId, pinned: bool) {
+        if let Some(overlay) = self.overlay() {
+            overlay.set_pinned(window_id, pinned);
+        }
+    }
+
+    // Show or hide the windows of the selected app. Returns `false` if windows aren't grouped by
+    // app.
+    pub fn toggle_group(&self) -> bool {
+        self.overlay()
+            .map(|overlay| overlay.toggle_group())
+            .unwrap_or(false)
+    }
+
+    // Take the window preview, if a window is being previewed, leaving no preview.
+    pub fn take_preview(&self) -> Option<sway::Preview> {
+        self.imp().preview.take()
+    }
+
+    pub fn set_preview(&self, preview: Option<sway::Preview>) {
+        self.imp().preview.replace(preview);
+    }
+
+    // Remember that peeking brought this window out of the scratchpad, so it can be sent back.
+    pub fn add_shown_scratchpad(&self, window_id: SwayWindowId) {
+        self.imp().shown_scratchpad.borrow_mut().push(window_id);
+    }
+
+    pub fn is_shown_scratchpad(&self, window_id: SwayWindowId) -> bool {
+        self.imp().shown_scratchpad.borrow().contains(&window_id)
+    }
+
+    // Forget that peeking showed this window, returning whether it did.
+    pub fn remove_shown_scratchpad(&self, window_id: SwayWindowId) -> bool {
+        let mut shown_scratchpad = self.imp().shown_scratchpad.borrow_mut();
+        let len = shown_scratchpad.len();
+        shown_scratchpad.retain(|id| *id != window_id);
+
+        shown_scratchpad.len() != len
+    }
+
+    // Take the windows that peeking brought out of the scratchpad, leaving none.
+    pub fn take_shown_scratchpad(&self) -> Vec<SwayWindowId> {
+        self.imp().shown_scratchpad.take()
     }
 }
 
 mod imp {
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
 
     use glib::Properties;
     use gtk::glib;
     use gtk::prelude::*;
     use gtk::subclass::prelude::*;
 
-    use swtchr::sway::SwayWindowId;
+    use swtchr::sway::{self, SwayWindowId};
 
     #[derive(Debug, Default, Properties)]
     #[properties(wrapper_type = super::Window)]
     pub struct Window {
         #[property(get, set)]
         window_id: Cell<SwayWindowId>,
+        #[property(get, set)]
+        scratchpad: Cell<bool>,
+        #[property(get, set)]
+        desktop_file_id: RefCell<String>,
+        #[property(get, set)]
+        workspace_name: RefCell<String>,
+        #[property(get, set)]
+        output_name: RefCell<String>,
+        #[property(get, set)]
+        initial_window_id: Cell<SwayWindowId>,
+        // The workspace or output that was focused when the window switcher opened, when it's
+        // showing workspaces or outputs instead of windows.
+        #[property(get, set)]
+        initial_workspace_name: RefCell<String>,
+        #[property(get, set)]
+        initial_output_name: RefCell<String>,
+        #[property(get, set)]
+        peeked: Cell<bool>,
+        pub(super) preview: RefCell<Option<sway::Preview>>,
+        // The windows that peeking brought out of the scratchpad.
+        pub(super) shown_scratchpad: RefCell<Vec<SwayWindowId>>,
     }
 
     #[glib::object_subclass]
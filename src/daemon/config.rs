@@ -1,12 +1,20 @@
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io::{self, Read, Write};
 use std::path::Path;
 use std::path::PathBuf;
 
-use eyre::{bail, eyre, WrapErr};
+use eyre::{eyre, WrapErr};
 use serde::Deserialize;
 
+use super::diagnostic::ConfigError;
+use super::keymap::{Keybind, KeymapConfig};
+use super::migrate::{migrate, save_migrated, CONFIG_VERSION};
+use super::rules::{Rule, RuleAction};
+use super::view::ViewConfig;
+use swtchr::sway::{self, SortOrder};
+
 const DEFAULT_CONFIG: &str = include_str!("../swtchr.toml");
 
 fn config_dir_path() -> eyre::Result<PathBuf> {
@@ -21,7 +29,7 @@ pub fn config_file_path() -> eyre::Result<PathBuf> {
     Ok(config_dir_path()?.join("swtchr.toml"))
 }
 
-fn css_file_path() -> eyre::Result<PathBuf> {
+pub fn css_file_path() -> eyre::Result<PathBuf> {
     Ok(config_dir_path()?.join("style.css"))
 }
 
@@ -44,53 +52,204 @@ pub fn user_css_override() -> eyre::Result<Option<String>> {
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct KeymapConfig {
-    pub dismiss: Option<String>,
-    pub select: Option<String>,
-    pub peek: Option<String>,
-    pub next: Option<String>,
-    pub prev: Option<String>,
-    pub peek_next: Option<String>,
-    pub peek_prev: Option<String>,
+// How the peek actions show the selected window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeekMode {
+    // Switch focus to the selected window.
+    Focus,
+
+    // Highlight the selected window in place without switching focus to it.
+    Preview,
 }
 
-#[derive(Debug, Deserialize)]
+// Any options missing from the config file fall back to their defaults, so that adding new
+// options doesn't break existing config files. These must match the defaults in `swtchr.toml`,
+// which is checked by a test.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
+    // Config files from before the format was versioned don't have this key.
+    #[serde(default)]
+    pub version: u32,
     pub icon_theme: Option<String>,
     pub font: Option<String>,
     pub urgent_first: bool,
+    pub show_scratchpad: bool,
+    pub group_by_app: bool,
+    pub sort: SortOrder,
+    pub frecency_half_life: u64,
+    pub pinned_apps: Vec<String>,
+    pub favorites: Vec<String>,
+    pub show_delay: u64,
+    pub peek_mode: PeekMode,
+    pub preview_opacity: f64,
     pub dismiss_on_release: bool,
     pub select_on_release: bool,
-    pub release_keys: Vec<String>,
+    pub release_keys: Vec<Keybind>,
+    pub auto_release_keys: bool,
     pub keymap: KeymapConfig,
+    pub rules: Vec<Rule>,
+    pub views: HashMap<String, ViewConfig>,
 }
 
-fn validate_keybind(name: &str, key: Option<&str>) -> eyre::Result<()> {
-    if let Some(key) = key {
-        if gtk::accelerator_parse(key).is_none() {
-            bail!("Invalid keybind for `{}`: {}", name, key);
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            icon_theme: None,
+            font: None,
+            urgent_first: true,
+            show_scratchpad: true,
+            group_by_app: false,
+            sort: SortOrder::Recent,
+            frecency_half_life: 3600,
+            pinned_apps: Vec::new(),
+            favorites: Vec::new(),
+            show_delay: 0,
+            peek_mode: PeekMode::Focus,
+            preview_opacity: 0.5,
+            dismiss_on_release: true,
+            select_on_release: true,
+            release_keys: vec![Keybind::new("Super_L")],
+            auto_release_keys: false,
+            keymap: KeymapConfig::default(),
+            rules: Vec::new(),
+            views: HashMap::new(),
         }
     }
+}
+
+fn read_file(path: &Path) -> eyre::Result<String> {
+    let mut file = fs::File::open(path).wrap_err("Failed opening the config file for reading.")?;
+
+    // A conservative estimate of the size of the buffer we'll need.
+    let mut file_contents = String::with_capacity(DEFAULT_CONFIG.len() * 2);
 
-    Ok(())
+    file.read_to_string(&mut file_contents)
+        .wrap_err("Failed reading the contents of the config file.")?;
+
+    Ok(file_contents)
 }
 
 impl Config {
-    fn validate(&self) -> eyre::Result<()> {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.version > CONFIG_VERSION {
+            return Err(ConfigError::new(format!(
+                "This config file is for a newer version of swtchr (config version {}, but this version of swtchr supports up to {}).",
+                self.version, CONFIG_VERSION,
+            )));
+        }
+
+        if self.frecency_half_life == 0 {
+            return Err(ConfigError::new(
+                "The `frecency_half_life` must be greater than 0.",
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.preview_opacity) {
+            return Err(ConfigError::new(format!(
+                "The `preview_opacity` must be between 0 and 1, but it's {}.",
+                self.preview_opacity,
+            )));
+        }
+
         for key in &self.release_keys {
-            validate_keybind("release_keys", Some(key))?
+            key.validate("release_keys")?
+        }
+
+        for rule in &self.rules {
+            rule.validate()?;
+        }
+
+        for view in self.views.values() {
+            view.validate()?;
+        }
+
+        self.keymap.validate()
+    }
+
+    fn parse(path: &Path, contents: &str) -> Result<Self, ConfigError> {
+        let config: Config = toml::from_str(contents)
+            .map_err(|err| ConfigError::from_toml(&err).locate(path, contents))?;
+
+        config
+            .validate()
+            .map_err(|err| err.locate(path, contents))?;
+
+        Ok(config)
+    }
+
+    // Parse a config file that was migrated to the current version of the config format.
+    //
+    // Migrating only adds to the file, so any mistake in the migrated file is also in the
+    // original. We report it against the original so that the line numbers match the file the
+    // user sees.
+    fn parse_migrated(path: &Path, original: &str, migrated: &str) -> Result<Self, ConfigError> {
+        Self::parse(path, migrated).map_err(|err| Self::parse(path, original).err().unwrap_or(err))
+    }
+
+    // What the first rule that matches this window says to do with it, if any rules match.
+    fn rule_action(&self, window: &sway::Window) -> Option<RuleAction> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(window))
+            .map(|rule| rule.action)
+    }
+
+    // Where the window goes among the pinned windows, if it's pinned. Pinned windows come before
+    // the windows of pinned apps, which are in the order the apps are listed in the config.
+    fn pin_position(&self, window: &sway::Window) -> Option<(usize, u64)> {
+        match window.pin {
+            Some(number) => Some((0, number)),
+            None => self
+                .pinned_apps
+                .iter()
+                .position(|app_id| window.app_id.as_ref() == Some(app_id))
+                .map(|index| (index + 1, 0)),
+        }
+    }
+
+    // Remove the windows that shouldn't be shown in the window switcher, move the pinned ones to
+    // the front, and move the ones that should be shown last to the end, keeping the windows in
+    // the same order otherwise.
+    pub fn filter_windows(&self, windows: Vec<sway::Window>) -> Vec<sway::Window> {
+        let mut pinned = Vec::new();
+        let mut shown = Vec::with_capacity(windows.len());
+        let mut last = Vec::new();
+
+        for window in windows {
+            if !self.show_scratchpad && window.scratchpad {
+                continue;
+            }
+
+            match self.rule_action(&window) {
+                Some(RuleAction::Hide) => {}
+                Some(RuleAction::Last) => last.push(window),
+                None if self.pin_position(&window).is_some() => pinned.push(window),
+                None => shown.push(window),
+            }
         }
 
-        validate_keybind("dismiss", self.keymap.dismiss.as_deref())?;
-        validate_keybind("select", self.keymap.select.as_deref())?;
-        validate_keybind("peek", self.keymap.peek.as_deref())?;
-        validate_keybind("next", self.keymap.next.as_deref())?;
-        validate_keybind("prev", self.keymap.prev.as_deref())?;
-        validate_keybind("peek_next", self.keymap.peek_next.as_deref())?;
-        validate_keybind("peek_prev", self.keymap.peek_prev.as_deref())?;
+        // This is a stable sort, so the windows of each pinned app stay in the same order.
+        pinned.sort_by_key(|window| self.pin_position(window));
+
+        pinned.extend(shown);
+        pinned.extend(last);
 
-        Ok(())
+        pinned
+    }
+
+    // Read and validate the config file without creating it if it doesn't exist. If it's for an
+    // older version of the config format, this doesn't update the file.
+    pub fn check(path: &Path) -> eyre::Result<Self> {
+        let file_contents = read_file(path)?;
+
+        match migrate(&file_contents) {
+            Some(migrated) => Self::parse_migrated(path, &file_contents, &migrated),
+            None => Self::parse(path, &file_contents),
+        }
+        .wrap_err("There was a problem with the config file.")
     }
 
     pub fn read(path: &Path) -> eyre::Result<Self> {
@@ -103,7 +262,7 @@ impl Config {
                 .wrap_err("Failed creating the parent directory for the config file.")?,
         )?;
 
-        let config: Config = match fs::OpenOptions::new()
+        match fs::OpenOptions::new()
             .create_new(true)
             .write(true)
             .open(path)
@@ -114,33 +273,114 @@ impl Config {
                 file.write_all(DEFAULT_CONFIG.as_bytes())
                     .wrap_err("Failed writing the default config to the config file.")?;
 
-                toml::from_str(DEFAULT_CONFIG)
-                    .wrap_err("Failed deserializing default config. This is a bug.")?
+                Self::parse(path, DEFAULT_CONFIG)
+                    .wrap_err("Failed deserializing default config. This is a bug.")
             }
 
-            // The config file already exists. Read it.
+            // The config file already exists. Read it, updating it to the current version of the
+            // config format if necessary.
             Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
-                let mut file =
-                    fs::File::open(path).wrap_err("Failed opening the config file for reading.")?;
+                let file_contents = read_file(path)?;
 
-                // A conservative estimate of the size of the buffer we'll need.
-                let mut file_contents = String::with_capacity(DEFAULT_CONFIG.len() * 2);
+                let Some(migrated) = migrate(&file_contents) else {
+                    return Self::parse(path, &file_contents)
+                        .wrap_err("There was a problem with the config file.");
+                };
 
-                file.read_to_string(&mut file_contents)
-                    .wrap_err("Failed reading the contents of the config file.")?;
+                let config = Self::parse_migrated(path, &file_contents, &migrated)
+                    .wrap_err("There was a problem with the config file.")?;
 
-                toml::from_str(&file_contents)
-                    .wrap_err("Failed deserializing the config file. Is it valid TOML? Double-check your syntax.")?
+                tracing::info!(
+                    to = CONFIG_VERSION,
+                    "Migrating the config file to a new version."
+                );
+
+                // The config file might not be writable, like when it's managed by home-manager.
+                // That's fine, since we can migrate it again each time we read it.
+                if let Err(err) = save_migrated(path, &file_contents, &migrated) {
+                    tracing::warn!(
+                        "Failed saving the migrated config file. Using the migrated config without saving it: {:?}",
+                        err
+                    );
+                }
+
+                Ok(config)
             }
             Err(err) => {
-                Err(err).wrap_err("Failed trying to check if the config file already exists.")?
+                Err(err).wrap_err("Failed trying to check if the config file already exists.")
             }
-        };
+        }
+    }
+}
 
-        config
-            .validate()
-            .wrap_err("There was a problem with the config file.")?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::accels;
 
-        Ok(config)
+    fn parse(contents: &str) -> Config {
+        Config::parse(Path::new("swtchr.toml"), contents).unwrap()
+    }
+
+    #[test]
+    fn default_config_file_matches_defaults() {
+        assert_eq!(parse(DEFAULT_CONFIG), Config::default());
+    }
+
+    #[test]
+    fn missing_options_use_defaults() {
+        assert_eq!(
+            parse(&format!("version = {}\n", CONFIG_VERSION)),
+            Config::default()
+        );
+    }
+
+    // The options that config files had to set before the config format was versioned.
+    const V0_OPTIONS: &str = "\
+urgent_first = false
+dismiss_on_release = true
+select_on_release = true
+release_keys = [\"Super_L\"]
+
+";
+
+    #[test]
+    fn migrated_config_keeps_actions_unbound() {
+        let contents =
+            migrate(&format!("{}[keymap]\nnext = \"<Super>Tab\"\n", V0_OPTIONS)).unwrap();
+        let config = parse(&contents);
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(accels(&config.keymap, "dismiss").is_empty());
+        assert!(accels(&config.keymap, "prev").is_empty());
+        assert_eq!(accels(&config.keymap, "next"), ["<Super>Tab"]);
+
+        // Actions that didn't exist before the config format was versioned use their defaults.
+        assert_eq!(accels(&config.keymap, "pin"), ["<Super>p"]);
+    }
+
+    #[test]
+    fn migrated_config_error_points_to_original_file() {
+        let contents = format!("{}[keymap]\nnext = \"<Super>Tab\"\nbogus = 1\n", V0_OPTIONS);
+        let migrated = migrate(&contents).unwrap();
+
+        let err = Config::parse_migrated(Path::new("swtchr.toml"), &contents, &migrated)
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("swtchr.toml:8:"), "{}", err);
+    }
+
+    #[test]
+    fn old_config_with_new_default_keybind_parses() {
+        let contents = migrate(&format!(
+            "{}[keymap]\nselect = \"<Super>p\"\nnext = \"<Super>1\"\n",
+            V0_OPTIONS
+        ))
+        .unwrap();
+        let config = parse(&contents);
+
+        assert_eq!(accels(&config.keymap, "select"), ["<Super>p"]);
+        assert!(accels(&config.keymap, "pin").is_empty());
     }
 }
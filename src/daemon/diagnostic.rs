@@ -0,0 +1,351 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+// How similar an unknown key needs to be to a known key for us to suggest it.
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+#[derive(Debug, Clone)]
+struct Location {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+    line_text: String,
+    width: usize,
+}
+
+impl Location {
+    fn new(path: &Path, source: &str, span: Range<usize>) -> Self {
+        let start = span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let line_text = source[line_start..line_end].trim_end_matches('\r');
+
+        // Spans can cover multiple lines, like when they point at a whole table. We only
+        // underline the part on the first line.
+        let end = span.end.clamp(start, line_end);
+
+        Self {
+            path: path.to_owned(),
+            line: source[..line_start].matches('\n').count() + 1,
+            column: source[line_start..start].chars().count() + 1,
+            line_text: line_text.to_owned(),
+            width: source[start..end].chars().count().max(1),
+        }
+    }
+}
+
+// An error in the config file, pointing at where in the file the problem is.
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    message: String,
+    help: Option<String>,
+    span: Option<Range<usize>>,
+    // This is boxed to keep the error small, since it's returned from most of the config parsing
+    // code.
+    location: Option<Box<Location>>,
+}
+
+impl ConfigError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            help: None,
+            span: None,
+            location: None,
+        }
+    }
+
+    pub fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    // Resolve the span of this error to a line and column in the given config file.
+    pub fn locate(mut self, path: &Path, source: &str) -> Self {
+        if let Some(span) = &self.span {
+            self.location = Some(Box::new(Location::new(path, source, span.clone())));
+        }
+
+        self
+    }
+
+    pub fn from_toml(err: &toml::de::Error) -> Self {
+        let message = err.message().trim();
+
+        let config_err = match parse_unknown_field(message) {
+            Some((field, expected)) => {
+                let config_err = ConfigError::new(format!("Unknown key `{}`.", field));
+
+                match suggest(field, &expected) {
+                    Some(suggestion) => {
+                        config_err.with_help(format!("Did you mean `{}`?", suggestion))
+                    }
+                    None if expected.is_empty() => config_err,
+                    None => config_err.with_help(format!(
+                        "Expected one of: {}",
+                        expected
+                            .iter()
+                            .map(|key| format!("`{}`", key))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                }
+            }
+            None => match parse_missing_field(message) {
+                Some(field) => ConfigError::new(format!("Missing required key `{}`.", field)),
+                None => ConfigError::new(capitalize(message)),
+            },
+        };
+
+        match err.span() {
+            Some(span) => config_err.with_span(span),
+            None => config_err,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+
+        if let Some(location) = &self.location {
+            let line_number = location.line.to_string();
+            let gutter = " ".repeat(line_number.len());
+
+            write!(
+                f,
+                "\n{gutter}--> {}:{}:{}",
+                location.path.display(),
+                location.line,
+                location.column,
+            )?;
+            write!(f, "\n{gutter} |")?;
+            write!(f, "\n{line_number} | {}", location.line_text)?;
+            write!(
+                f,
+                "\n{gutter} | {}{}",
+                " ".repeat(location.column - 1),
+                "^".repeat(location.width),
+            )?;
+
+            if let Some(help) = &self.help {
+                write!(f, "\n{gutter} = help: {}", help)?;
+            }
+        } else if let Some(help) = &self.help {
+            write!(f, "\nhelp: {}", help)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn capitalize(message: &str) -> String {
+    let mut chars = message.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Return the backtick-quoted words in the string.
+fn quoted_words(message: &str) -> Vec<&str> {
+    message.split('`').skip(1).step_by(2).collect()
+}
+
+// Serde formats unknown field errors like:
+//
+// unknown field `dismis`, expected one of `dismiss`, `select`, `peek`
+fn parse_unknown_field(message: &str) -> Option<(&str, Vec<&str>)> {
+    let rest = message.strip_prefix("unknown field ")?;
+    let mut words = quoted_words(rest).into_iter();
+    let field = words.next()?;
+
+    Some((field, words.collect()))
+}
+
+// Serde formats missing field errors like:
+//
+// missing field `urgent_first`
+fn parse_missing_field(message: &str) -> Option<&str> {
+    let rest = message.strip_prefix("missing field ")?;
+    quoted_words(rest).into_iter().next()
+}
+
+// Find the known key most similar to the unknown one, if any are close enough.
+fn suggest<'a>(unknown: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|candidate| (*candidate, strsim::jaro_winkler(unknown, candidate)))
+        .filter(|(_, similarity)| *similarity >= SUGGESTION_THRESHOLD)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[test]
+    fn parse_unknown_field_with_expected_keys() {
+        assert_eq!(
+            parse_unknown_field("unknown field `dismis`, expected one of `dismiss`, `select`"),
+            Some(("dismis", vec!["dismiss", "select"]))
+        );
+    }
+
+    #[test]
+    fn parse_unknown_field_with_one_expected_key() {
+        assert_eq!(
+            parse_unknown_field("unknown field `nxt`, expected `next`"),
+            Some(("nxt", vec!["next"]))
+        );
+    }
+
+    #[test]
+    fn parse_unknown_field_with_no_expected_keys() {
+        assert_eq!(
+            parse_unknown_field("unknown field `next`, there are no fields"),
+            Some(("next", vec![]))
+        );
+    }
+
+    #[test]
+    fn parse_unknown_field_ignores_other_errors() {
+        assert_eq!(parse_unknown_field("missing field `next`"), None);
+        assert_eq!(parse_unknown_field("invalid type: integer `1`"), None);
+    }
+
+    #[test]
+    fn parse_missing_field_finds_field() {
+        assert_eq!(parse_missing_field("missing field `next`"), Some("next"));
+        assert_eq!(parse_missing_field("unknown field `next`"), None);
+    }
+
+    #[test]
+    fn suggest_picks_closest_key() {
+        assert_eq!(
+            suggest("dismis", &["select", "dismiss", "peek"]),
+            Some("dismiss")
+        );
+        assert_eq!(
+            suggest("peek_nxt", &["peek_next", "peek_prev"]),
+            Some("peek_next")
+        );
+    }
+
+    #[test]
+    fn suggest_nothing_when_no_key_is_close() {
+        assert_eq!(suggest("wallpaper", &["select", "dismiss", "peek"]), None);
+        assert_eq!(suggest("select", &[]), None);
+    }
+
+    #[test]
+    fn location_of_span() {
+        let source = "urgent_first = true\nfont = 12\n";
+        let location = Location::new(Path::new("swtchr.toml"), source, 27..29);
+
+        assert_eq!(location.line, 2);
+        assert_eq!(location.column, 8);
+        assert_eq!(location.line_text, "font = 12");
+        assert_eq!(location.width, 2);
+    }
+
+    #[test]
+    fn location_only_underlines_first_line() {
+        let source = "[keymap]\nnext = \"<Super>Tab\"\n";
+        let location = Location::new(Path::new("swtchr.toml"), source, 0..source.len());
+
+        assert_eq!(location.line, 1);
+        assert_eq!(location.column, 1);
+        assert_eq!(location.line_text, "[keymap]");
+        assert_eq!(location.width, 8);
+    }
+
+    #[test]
+    fn location_counts_characters_not_bytes() {
+        let source = "font = \"Fira Sans ü\"\r\nfoo = 1\r\n";
+        let start = source.find("foo").unwrap();
+        let location = Location::new(Path::new("swtchr.toml"), source, start..start + 3);
+
+        assert_eq!(location.line, 2);
+        assert_eq!(location.column, 1);
+        assert_eq!(location.line_text, "foo = 1");
+
+        let start = source.find('"').unwrap();
+        let end = start + "\"Fira Sans ü\"".len();
+        let location = Location::new(Path::new("swtchr.toml"), source, start..end);
+
+        assert_eq!(location.column, 8);
+        assert_eq!(location.width, 13);
+    }
+
+    #[test]
+    fn location_of_span_at_end_of_file() {
+        let source = "font = 12";
+        let location = Location::new(Path::new("swtchr.toml"), source, 9..9);
+
+        assert_eq!(location.line, 1);
+        assert_eq!(location.column, 10);
+        assert_eq!(location.width, 1);
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Keymap {
+        dismiss: Option<String>,
+        select: Option<String>,
+    }
+
+    // Make sure we understand the error messages that serde and toml actually produce.
+    #[test]
+    fn from_toml_unknown_field() {
+        let source = "dismis = \"Escape\"\n";
+        let err = toml::from_str::<Keymap>(source).unwrap_err();
+        let config_err = ConfigError::from_toml(&err);
+
+        assert_eq!(config_err.message, "Unknown key `dismis`.");
+        assert_eq!(config_err.help.as_deref(), Some("Did you mean `dismiss`?"));
+        assert_eq!(config_err.span, Some(0..6));
+    }
+
+    #[test]
+    fn from_toml_unknown_field_without_suggestion() {
+        let source = "wallpaper = \"beach.png\"\n";
+        let err = toml::from_str::<Keymap>(source).unwrap_err();
+        let config_err = ConfigError::from_toml(&err);
+
+        assert_eq!(config_err.message, "Unknown key `wallpaper`.");
+        assert_eq!(
+            config_err.help.as_deref(),
+            Some("Expected one of: `dismiss`, `select`")
+        );
+    }
+
+    #[test]
+    fn display_points_to_location() {
+        let source = "urgent_first = true\nfont = 12\n";
+        let config_err = ConfigError::new("Invalid type.")
+            .with_span(27..29)
+            .with_help("Use a string.")
+            .locate(Path::new("swtchr.toml"), source);
+
+        assert_eq!(
+            config_err.to_string(),
+            "Invalid type.\n --> swtchr.toml:2:8\n  |\n2 | font = 12\n  |        ^^\n  = help: Use a string."
+        );
+    }
+}
@@ -1,18 +1,22 @@
-use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 
 use eyre::{eyre, WrapErr};
-use gtk::gdk::Display;
-use gtk::gio::ActionEntry;
+use gtk::gdk::{self, Display};
+use gtk::gio::{ActionEntry, DesktopAppInfo};
 use gtk::glib::{self, clone};
 use gtk::{prelude::*, CssProvider};
-use gtk::{Application, DirectionType, EventControllerKey, Settings};
+use gtk::{Application, DirectionType, EventControllerKey, IMMulticontext, Settings};
 use gtk4_layer_shell::{KeyboardMode, Layer, LayerShell};
 
 use super::components::Window;
-use super::config::Config;
+use super::config::{css_file_path, user_css_override, Config, PeekMode};
 use super::ipc;
-use crate::config::user_css_override;
+use super::release::ReleaseKeys;
+use super::watch::{self, ConfigFile};
 use crate::WINDOW_TITLE;
 use swtchr::ipc::Command as SwtchrCommand;
 use swtchr::sway::{self, SwayMode, WindowSubscription};
@@ -25,45 +29,361 @@ fn set_settings(config: &Config) {
     settings.set_gtk_font_name(config.font.as_deref());
 }
 
-type DisplayCallback = Box<dyn Fn()>;
+// The config is shared so that it can be swapped out when the config file is reloaded.
+type SharedConfig = Rc<RefCell<Config>>;
 
-fn register_actions(app_window: &Window, on_display: DisplayCallback) {
-    // Make the overlay visible and capture keyboard events.
-    let show = ActionEntry::builder("show")
-        .activate(move |window: &Window, _, _| {
-            on_display();
-            window.set_keyboard_mode(KeyboardMode::Exclusive);
-            window.set_visible(true);
+// Hide the overlay and release control of the keyboard.
+fn hide_overlay(window: &Window) {
+    // Failing to clean up after peeking shouldn't stop us from hiding the overlay and giving the
+    // keybinds back to Sway, so we log these errors instead of panicking.
+    if let Some(preview) = window.take_preview() {
+        if let Err(err) = sway::clear_preview(preview) {
+            tracing::error!("Failed clearing the Sway window preview: {:#}", err);
+        }
+    }
+
+    // Send back any windows that peeking brought out of the scratchpad. A window might have
+    // closed or been moved out of the scratchpad since, so we skip any we can't send back.
+    for window_id in window.take_shown_scratchpad() {
+        if let Err(err) = sway::hide_scratchpad_window(window_id) {
+            tracing::warn!(
+                window = ?window_id,
+                "Failed moving the window back to the Sway scratchpad: {:#}",
+                err
+            );
+        }
+    }
+
+    window.set_keyboard_mode(KeyboardMode::None);
+    window.set_visible(false);
+
+    // Switch Sway back to the default keybind mode, releasing exclusive control over the
+    // keybinds.
+    sway::switch_mode(SwayMode::Default)
+        .expect("Failed switching Sway back to the default keybind mode.");
+}
+
+// Show the selected window without hiding the overlay, either by switching to it or by
+// previewing it in place.
+fn peek_window(window: &Window, config: &SharedConfig) {
+    let config = config.borrow();
+    let workspace_name = window.workspace_name();
+    let output_name = window.output_name();
+
+    match config.peek_mode {
+        // There's no way to preview a workspace or output without switching to it.
+        _ if !workspace_name.is_empty() => {
+            sway::switch_workspace(&workspace_name).expect("Failed changing Sway workspace focus.");
+            window.set_peeked(true);
+        }
+        _ if !output_name.is_empty() => {
+            sway::switch_output(&output_name).expect("Failed changing Sway output focus.");
+            window.set_peeked(true);
+        }
+        // Focusing a window in the scratchpad doesn't show it. Showing it again would hide it, so
+        // only show it the first time.
+        PeekMode::Focus
+            if window.scratchpad() && !window.is_shown_scratchpad(window.window_id()) =>
+        {
+            sway::show_scratchpad_window(window.window_id())
+                .expect("Failed showing the Sway scratchpad window.");
+            window.add_shown_scratchpad(window.window_id());
+            window.set_peeked(true);
+        }
+        PeekMode::Focus => {
+            sway::switch_window(window.window_id()).expect("Failed changing Sway window focus.");
+            window.set_peeked(true);
+        }
+        PeekMode::Preview => {
+            let preview = sway::preview_window(
+                window.window_id(),
+                config.preview_opacity,
+                window.take_preview(),
+            )
+            .expect("Failed previewing the Sway window.");
+            window.set_preview(preview);
+        }
+    }
+}
+
+// Find the desktop entries of the user's favorite apps, along with the most recently used window of
+// each one that's running.
+fn favorite_apps(
+    config: &Config,
+    windows: &[sway::Window],
+) -> Vec<(DesktopAppInfo, Option<sway::Window>)> {
+    if config.favorites.is_empty() {
+        return Vec::new();
+    }
+
+    config
+        .favorites
+        .iter()
+        .filter_map(|desktop_file_id| {
+            let app_info = DesktopAppInfo::new(desktop_file_id);
+
+            if app_info.is_none() {
+                tracing::warn!(
+                    %desktop_file_id,
+                    "Could not find the desktop entry of a favorite app."
+                );
+            }
+
+            app_info
+        })
+        .map(|app_info| {
+            let window = windows
+                .iter()
+                .filter(|window| sway::is_app_window(&app_info, window))
+                .min_by_key(|window| cmp::Reverse(window.last_used))
+                .cloned();
+
+            (app_info, window)
         })
+        .collect()
+}
+
+// Make the overlay visible and capture keyboard events. The overlay should already be populated.
+//
+// If there's a `show_delay`, we capture keyboard events right away, but keep the overlay
+// transparent until the delay has passed. That way, a quick tap that selects a window before then
+// never draws the overlay at all.
+fn show_overlay(
+    window: &Window,
+    config: &SharedConfig,
+    subscription: &WindowSubscription,
+    show_count: &Rc<Cell<u64>>,
+) {
+    let show_delay = Duration::from_millis(config.borrow().show_delay);
+
+    show_count.set(show_count.get() + 1);
+    let this_show = show_count.get();
+
+    // Stop the window list from changing while the user is peeking at windows.
+    subscription
+        .freeze()
+        .expect("Failed freezing the window list.");
+
+    window.set_opacity(if show_delay.is_zero() { 1.0 } else { 0.0 });
+    window.set_keyboard_mode(KeyboardMode::Exclusive);
+    window.set_visible(true);
+
+    if show_delay.is_zero() {
+        return;
+    }
+
+    glib::timeout_add_local_once(
+        show_delay,
+        clone!(@weak window, @strong show_count => move || {
+            if window.is_visible() && show_count.get() == this_show {
+                window.set_opacity(1.0);
+            }
+        }),
+    );
+}
+
+fn register_actions(
+    app_window: &Window,
+    config: SharedConfig,
+    subscription: Rc<WindowSubscription>,
+) {
+    // Counts how many times the overlay has been shown, so that a delayed reveal from a previous
+    // time it was shown doesn't reveal it early.
+    let show_count = Rc::new(Cell::new(0u64));
+
+    // Show all windows.
+    let show = ActionEntry::builder("show")
+        .activate(clone!(@strong config, @strong subscription, @strong show_count => move |window: &Window, _, _| {
+            // Update the list of windows in the window switcher right before we display it.
+            let windows = subscription.get_window_list(config.borrow().sort).expect("Failed getting window list to populate window switcher overlay.");
+            let favorites = favorite_apps(&config.borrow(), &windows);
+            let windows = config.borrow().filter_windows(windows);
+            let focused = sway::focused_window().expect("Failed getting the focused window.").unwrap_or_default();
+            window.update_windows(&windows, config.borrow().group_by_app, focused);
+            window.set_favorites(&favorites);
+
+            show_overlay(window, &config, &subscription, &show_count);
+        }))
         .build();
 
-    // Hide the overlay and release control of the keyboard.
-    let dismiss = ActionEntry::builder("dismiss")
-        .activate(|window: &Window, _, _| {
-            window.set_keyboard_mode(KeyboardMode::None);
-            window.set_visible(false);
+    // Show only the windows of the focused app. There's no point in grouping them by app.
+    let show_app = ActionEntry::builder("show-app")
+        .activate(clone!(@strong config, @strong subscription, @strong show_count => move |window: &Window, _, _| {
+            let windows = subscription.get_app_window_list(config.borrow().sort).expect("Failed getting window list to populate window switcher overlay.");
+            let windows = config.borrow().filter_windows(windows);
+            let focused = sway::focused_window().expect("Failed getting the focused window.").unwrap_or_default();
+            window.update_windows(&windows, false, focused);
 
-            // Switch Sway back to the default keybind mode, releasing exclusive control over the
-            // keybinds.
-            sway::switch_mode(SwayMode::Default)
-                .expect("Failed switching Sway back to the default keybind mode.");
-        })
+            show_overlay(window, &config, &subscription, &show_count);
+        }))
+        .build();
+
+    // Show workspaces instead of windows.
+    let show_workspaces = ActionEntry::builder("show-workspaces")
+        .activate(clone!(@strong config, @strong subscription, @strong show_count => move |window: &Window, _, _| {
+            let workspaces = subscription.get_workspace_list().expect("Failed getting workspace list to populate window switcher overlay.");
+            let focused = sway::focused_window().expect("Failed getting the focused window.").unwrap_or_default();
+            let focused_workspace = sway::focused_workspace().expect("Failed getting the focused workspace.").unwrap_or_default();
+            window.update_workspaces(&workspaces, focused, &focused_workspace);
+
+            show_overlay(window, &config, &subscription, &show_count);
+        }))
         .build();
 
-    // Switch to the selected window and hide the overlay.
+    // Show outputs instead of windows.
+    let show_outputs = ActionEntry::builder("show-outputs")
+        .activate(clone!(@strong config, @strong subscription, @strong show_count => move |window: &Window, _, _| {
+            let outputs = subscription.get_output_list().expect("Failed getting output list to populate window switcher overlay.");
+            let focused = sway::focused_window().expect("Failed getting the focused window.").unwrap_or_default();
+            let focused_output = sway::focused_output().expect("Failed getting the focused output.").unwrap_or_default();
+            window.update_outputs(&outputs, focused, &focused_output);
+
+            show_overlay(window, &config, &subscription, &show_count);
+        }))
+        .build();
+
+    // Show a view defined in the config file.
+    let show_view = ActionEntry::builder("show-view")
+        .parameter_type(Some(&String::static_variant_type()))
+        .activate(clone!(@strong config, @strong subscription, @strong show_count => move |window: &Window, _, parameter| {
+            let name = parameter
+                .and_then(|parameter| parameter.get::<String>())
+                .expect("The show-view action was activated without a view name.");
+
+            let Some(view) = config.borrow().views.get(&name).cloned() else {
+                tracing::error!(view = %name, "There is no view with this name in the config file.");
+
+                // The client already switched Sway to the swtchr keybind mode, and we're not
+                // going to show the overlay to switch it back.
+                sway::switch_mode(SwayMode::Default)
+                    .expect("Failed switching Sway back to the default keybind mode.");
+
+                return;
+            };
+
+            let windows = subscription.get_window_list(view.sort.unwrap_or(config.borrow().sort)).expect("Failed getting window list to populate window switcher overlay.");
+            let focused_output = sway::focused_output().expect("Failed getting the focused output.");
+            let windows = view.filter_windows(config.borrow().filter_windows(windows), focused_output.as_deref());
+            let focused = sway::focused_window().expect("Failed getting the focused window.").unwrap_or_default();
+            window.update_windows(&windows, view.group_by_app.unwrap_or(config.borrow().group_by_app), focused);
+
+            show_overlay(window, &config, &subscription, &show_count);
+        }))
+        .build();
+
+    // Hide the overlay without switching windows. If the user peeked at any windows, workspaces, or
+    // outputs, switch back to the one that was focused when the overlay was opened.
+    let dismiss = ActionEntry::builder("dismiss")
+        .activate(
+            clone!(@strong subscription => move |window: &Window, _, _| {
+                // The overlay may have already been hidden by `select`, like when both
+                // `select_on_release` and `dismiss_on_release` are set.
+                if !window.is_visible() {
+                    return;
+                }
+
+                // If the user is searching for a window, clear the search instead.
+                if !window.search_query().is_empty() {
+                    window.set_search_query("");
+                    return;
+                }
+
+                let initial_window_id = window.initial_window_id();
+                let initial_workspace_name = window.initial_workspace_name();
+                let initial_output_name = window.initial_output_name();
+
+                // If the user peeked at workspaces or outputs, go back to the one they started on.
+                // There may not have been a focused window, like on an empty workspace.
+                if window.peeked() {
+                    if !initial_workspace_name.is_empty() {
+                        sway::switch_workspace(&initial_workspace_name)
+                            .expect("Failed restoring Sway workspace focus.");
+                    } else if !initial_output_name.is_empty() {
+                        sway::switch_output(&initial_output_name)
+                            .expect("Failed restoring Sway output focus.");
+                    } else {
+                        sway::switch_window(initial_window_id)
+                            .expect("Failed restoring Sway window focus.");
+                    }
+                }
+
+                subscription.thaw(initial_window_id).expect("Failed unfreezing the window list.");
+                hide_overlay(window);
+            }),
+        )
+        .build();
+
+    // Switch to the selected window, workspace, or output, or launch the selected app, and hide
+    // the overlay.
     let select = ActionEntry::builder("select")
-        .activate(|window: &Window, _, _| {
-            sway::switch_window(window.window_id()).expect("Failed changing Sway window focus.");
-            WidgetExt::activate_action(window, "win.dismiss", None)
-                .expect("Failed activating GTK action to dismiss the window switcher overlay.");
+        .activate(move |window: &Window, _, _| {
+            if !window.is_visible() {
+                return;
+            }
+
+            let desktop_file_id = window.desktop_file_id();
+            let workspace_name = window.workspace_name();
+            let output_name = window.output_name();
+
+            // The user selected a workspace or output rather than a window.
+            if !workspace_name.is_empty() || !output_name.is_empty() {
+                if workspace_name.is_empty() {
+                    sway::switch_output(&output_name).expect("Failed changing Sway output focus.");
+                } else {
+                    sway::switch_workspace(&workspace_name)
+                        .expect("Failed changing Sway workspace focus.");
+                }
+
+                // Switching workspaces or outputs focuses a window there, but the window list
+                // ignores focus events until we unfreeze it.
+                let focused = sway::focused_window()
+                    .expect("Failed getting the focused window.")
+                    .unwrap_or_default();
+
+                subscription
+                    .thaw(focused)
+                    .expect("Failed unfreezing the window list.");
+                hide_overlay(window);
+
+                return;
+            }
+
+            // The user searched for an app with no open windows and selected it to launch it.
+            if !desktop_file_id.is_empty() {
+                let app_info = DesktopAppInfo::new(&desktop_file_id)
+                    .expect("Failed finding the desktop entry of the app to launch.");
+
+                sway::launch_app(&app_info).expect("Failed launching the app.");
+                subscription
+                    .thaw(window.initial_window_id())
+                    .expect("Failed unfreezing the window list.");
+                hide_overlay(window);
+
+                return;
+            }
+
+            let window_id = window.window_id();
+
+            // If peeking already brought the window out of the scratchpad, keep it out.
+            if window.scratchpad() && !window.remove_shown_scratchpad(window_id) {
+                sway::show_scratchpad_window(window_id)
+                    .expect("Failed showing the Sway scratchpad window.");
+            } else {
+                sway::switch_window(window_id).expect("Failed changing Sway window focus.");
+            }
+
+            subscription
+                .thaw(window_id)
+                .expect("Failed unfreezing the window list.");
+            hide_overlay(window);
         })
         .build();
 
     // Switch to the selected window without hiding the overlay.
     let peek = ActionEntry::builder("peek")
-        .activate(|window: &Window, _, _| {
-            sway::switch_window(window.window_id()).expect("Failed changing Sway window focus.");
-        })
+        .activate(clone!(@strong config => move |window: &Window, _, _| {
+            peek_window(window, &config);
+        }))
         .build();
 
     // Select the next window in the list.
@@ -82,66 +402,263 @@ fn register_actions(app_window: &Window, on_display: DisplayCallback) {
 
     // Select the next window in the list and switch to it without hiding the overlay.
     let peek_next = ActionEntry::builder("peek-next")
-        .activate(|window: &Window, _, _| {
+        .activate(clone!(@strong config => move |window: &Window, _, _| {
             window.child_focus(DirectionType::TabForward);
-            sway::switch_window(window.window_id()).expect("Failed changing Sway window focus.");
-        })
+            peek_window(window, &config);
+        }))
         .build();
 
     // Select the previous window in the list and switch to it without hiding the overlay.
     let peek_prev = ActionEntry::builder("peek-prev")
-        .activate(|window: &Window, _, _| {
+        .activate(clone!(@strong config => move |window: &Window, _, _| {
             window.child_focus(DirectionType::TabBackward);
-            sway::switch_window(window.window_id()).expect("Failed changing Sway window focus.");
+            peek_window(window, &config);
+        }))
+        .build();
+
+    // Show or hide the windows of the selected app, when windows are grouped by app. Otherwise,
+    // select the next window in the list, so that this keybind can cycle through the windows of
+    // an app when only showing the focused app's windows.
+    let expand = ActionEntry::builder("expand")
+        .activate(|window: &Window, _, _| {
+            if !window.toggle_group() {
+                window.child_focus(DirectionType::TabForward);
+            }
         })
         .build();
 
+    // Pin the selected window to the front of the window switcher, or unpin it if it's already
+    // pinned.
+    let pin = ActionEntry::builder("pin")
+        .activate(|window: &Window, _, _| {
+            let window_id = window.window_id();
+
+            if window_id.is_null() {
+                return;
+            }
+
+            let pinned = sway::toggle_pin(window_id).expect("Failed pinning the Sway window.");
+            window.set_pinned(window_id, pinned);
+        })
+        .build();
+
+    // Select the window with the given number, counting from 1. When windows are selected on
+    // release, there's no reason to wait for the release, so switch to it right away.
+    let jump = ActionEntry::builder("jump")
+        .parameter_type(Some(&i32::static_variant_type()))
+        .activate(
+            clone!(@strong config => move |window: &Window, _, parameter| {
+                let index = parameter
+                    .and_then(|parameter| parameter.get::<i32>())
+                    .expect("The jump action was activated without a window number.");
+
+                if !window.is_visible() || !window.select_index(index as usize) {
+                    return;
+                }
+
+                if config.borrow().select_on_release {
+                    WidgetExt::activate_action(window, "win.select", None)
+                        .expect("Failed activating GTK action to switch window focus.");
+                }
+            }),
+        )
+        .build();
+
     app_window.add_action_entries([
-        show, dismiss, select, peek, next, prev, peek_next, peek_prev,
+        show,
+        show_app,
+        show_workspaces,
+        show_outputs,
+        show_view,
+        dismiss,
+        select,
+        peek,
+        next,
+        prev,
+        peek_next,
+        peek_prev,
+        expand,
+        pin,
+        jump,
     ]);
 }
 
-fn register_key_release_controller(config: &Config, window: &Window) {
-    let dismiss_on_release = config.dismiss_on_release;
-    let select_on_release = config.select_on_release;
-
-    // When watching for key release events, we ignore modifiers. This is a workaround for some
-    // inconsistent behavior I've noticed between different versions of GTK4:
-    //
-    // - On v4.6, releasing the Super key emits a key release signal with `Super_L` as the key and
-    //   `<Super>` as the modifier.
-    // - On v4.12, releasing the Super key emits a key release signal with `Super_L` as the key and
-    //   no modifier.
-    //
-    // I've only tested these two versions, since they're what are available in my system package
-    // manager and nixpkgs respectively.
-
-    let release_keys = config
-        .release_keys
-        .iter()
-        .filter_map(gtk::accelerator_parse)
-        .map(|(key, _)| key)
-        .collect::<HashSet<_>>();
+// Switch to the selected window and/or dismiss the overlay, depending on the config, because the
+// release keys were released.
+fn activate_release_actions(window: &Window, config: &SharedConfig) {
+    // Release the borrow before activating any actions, since they may need to read the config
+    // too.
+    let (select_on_release, dismiss_on_release) = {
+        let config = config.borrow();
+        (config.select_on_release, config.dismiss_on_release)
+    };
+
+    if select_on_release {
+        WidgetExt::activate_action(window, "win.select", None)
+            .expect("Failed activating GTK action to switch window focus on key release.");
+    }
 
-    if !dismiss_on_release && !select_on_release {
-        return;
+    if dismiss_on_release {
+        // Clear the search query first so that the `dismiss` action actually hides the overlay.
+        window.set_search_query("");
+
+        WidgetExt::activate_action(window, "win.dismiss", None)
+            .expect("Failed activating GTK action to dismiss window switcher on key release.");
+    }
+}
+
+fn keyboard_device(window: &Window) -> Option<gdk::Device> {
+    WidgetExt::display(window)
+        .default_seat()
+        .and_then(|seat| seat.keyboard())
+}
+
+// Check the keyboard's modifier state to see whether the release keys are up.
+fn release_modifiers_are_up(
+    window: &Window,
+    keyboard: &gdk::Device,
+    config: &SharedConfig,
+) -> bool {
+    let state = keyboard.modifier_state();
+    let is_released =
+        ReleaseKeys::new(&config.borrow(), &WidgetExt::display(window)).is_released(state);
+
+    if is_released {
+        tracing::debug!(?state, "Release modifiers are up.");
     }
 
+    is_released
+}
+
+fn register_key_release_controller(config: SharedConfig, window: &Window) {
     let controller = EventControllerKey::new();
 
-    controller.connect_key_released(clone!(@weak window => move |_, actual_key, _, _| {
-        if !release_keys.contains(&actual_key) {
+    controller.connect_key_released(
+        clone!(@weak window, @strong config => move |_, actual_key, keycode, _| {
+            // Read the config each time a key is released so that changes to the config file are
+            // picked up without restarting the daemon.
+            let release_keys = ReleaseKeys::new(&config.borrow(), &WidgetExt::display(&window));
+
+            // When watching for key release events, we ignore modifiers. This is a workaround for
+            // some inconsistent behavior I've noticed between different versions of GTK4:
+            //
+            // - On v4.6, releasing the Super key emits a key release signal with `Super_L` as the
+            //   key and `<Super>` as the modifier.
+            // - On v4.12, releasing the Super key emits a key release signal with `Super_L` as the
+            //   key and no modifier.
+            //
+            // I've only tested these two versions, since they're what are available in my system
+            // package manager and nixpkgs respectively.
+            if !release_keys.is_release_key(actual_key, keycode) {
+                tracing::trace!(key = %actual_key.name().unwrap_or_default(), keycode, "Ignoring release of a key that is not a release key.");
+                return;
+            }
+
+            tracing::debug!(key = %actual_key.name().unwrap_or_default(), keycode, "Release key was released.");
+
+            activate_release_actions(&window, &config);
+        }),
+    );
+
+    window.add_controller(controller);
+
+    // The key that gets released doesn't always match the release keys, like when the user has
+    // remapped their modifier keys. So we also watch the modifier state of the keyboard, which
+    // changes the same way no matter which physical key activates a modifier.
+    let keyboard = match keyboard_device(window) {
+        Some(keyboard) => keyboard,
+        None => {
+            tracing::warn!(
+                "Could not find a keyboard device. Only watching for release keys by name."
+            );
+            return;
+        }
+    };
+
+    keyboard.connect_modifier_state_notify(
+        clone!(@weak window, @strong config => move |keyboard| {
+            // We only have keyboard focus, and thus an up-to-date modifier state, while the
+            // overlay is visible.
+            if !window.is_visible() {
+                return;
+            }
+
+            if release_modifiers_are_up(&window, keyboard, &config) {
+                activate_release_actions(&window, &config);
+            }
+        }),
+    );
+
+    // If the user taps `<Super>Tab` quickly, they can release the modifier before the overlay
+    // grabs the keyboard, in which case we never see it get released and the overlay stays open.
+    // So once we have keyboard focus, check whether the release keys are already up. This makes a
+    // quick tap switch straight to the previous window.
+    window.connect_is_active_notify(move |window| {
+        if !window.is_active() {
             return;
         }
 
-        if select_on_release {
-            WidgetExt::activate_action(&window, "win.select", None)
-                .expect("Failed activating GTK action to switch window focus on key release.");
+        // The compositor sends the current modifier state right after it gives us keyboard
+        // focus, so wait until that's been processed.
+        glib::idle_add_local_once(
+            clone!(@weak window, @weak keyboard, @strong config => move || {
+                if !window.is_visible() {
+                    return;
+                }
+
+                if release_modifiers_are_up(&window, &keyboard, &config) {
+                    tracing::debug!("Release keys were already up when the overlay got keyboard focus.");
+                    activate_release_actions(&window, &config);
+                }
+            }),
+        );
+    });
+}
+
+// Let the user search for windows by typing while the overlay is open. Text goes through an input
+// method so that users can search for titles in any script.
+fn register_search_controller(window: &Window) {
+    let im_context = IMMulticontext::new();
+    im_context.set_client_widget(Some(window));
+
+    let controller = EventControllerKey::new();
+    controller.set_im_context(Some(&im_context));
+
+    im_context.connect_commit(clone!(@weak window => move |_, text| {
+        let text = text.chars().filter(|c| !c.is_control()).collect::<String>();
+
+        if !window.is_visible() || text.is_empty() {
+            return;
         }
 
-        if dismiss_on_release {
-            WidgetExt::activate_action(&window, "win.dismiss", None)
-                .expect("Failed activating GTK action to dismiss window switcher on key release.");
+        window.set_search_query(&(window.search_query() + &text));
+    }));
+
+    controller.connect_key_pressed(
+        clone!(@weak window => @default-return glib::Propagation::Proceed, move |_, key, _, _| {
+            if key != gdk::Key::BackSpace {
+                return glib::Propagation::Proceed;
+            }
+
+            let mut query = window.search_query();
+
+            if query.pop().is_none() {
+                return glib::Propagation::Proceed;
+            }
+
+            window.set_search_query(&query);
+
+            glib::Propagation::Stop
+        }),
+    );
+
+    // The input method needs to know when it's receiving input.
+    window.connect_visible_notify(clone!(@weak im_context => move |window| {
+        if window.is_visible() {
+            im_context.focus_in();
+        } else {
+            im_context.focus_out();
+            im_context.reset();
         }
     }));
 
@@ -155,63 +672,84 @@ fn register_ipc_command_handlers(window: &Window) -> eyre::Result<()> {
         while let Ok(msg) = receiver.recv().await {
             let action_result = match msg {
                 Ok(SwtchrCommand::Show) => WidgetExt::activate_action(&window, "win.show", None).map_err(eyre::Report::from),
+                Ok(SwtchrCommand::ShowApp) => WidgetExt::activate_action(&window, "win.show-app", None).map_err(eyre::Report::from),
+                Ok(SwtchrCommand::ShowWorkspaces) => WidgetExt::activate_action(&window, "win.show-workspaces", None).map_err(eyre::Report::from),
+                Ok(SwtchrCommand::ShowOutputs) => WidgetExt::activate_action(&window, "win.show-outputs", None).map_err(eyre::Report::from),
+                Ok(SwtchrCommand::ShowView(name)) => WidgetExt::activate_action(&window, "win.show-view", Some(&name.to_variant())).map_err(eyre::Report::from),
                 Err(err) => {
-                    eprintln!("Error receiving IPC command from the swtchr client: {}", err);
+                    tracing::error!("Error receiving IPC command from the swtchr client: {:#}", err);
                     continue;
                 },
             };
 
             if let Err(err) = action_result {
-                eprintln!("Error dispatching IPC command from the swtchr client: {}", err);
+                tracing::error!("Error dispatching IPC command from the swtchr client: {:#}", err);
             }
         }
 
-        eprintln!("Cannot receive next command: Channel unexpectedly closed.");
+        tracing::error!("Cannot receive next command: Channel unexpectedly closed.");
     }));
 
     Ok(())
 }
 
 fn register_keybinds(config: &Config, app: &Application) {
-    if let Some(key) = &config.keymap.dismiss {
-        app.set_accels_for_action("win.dismiss", &[key]);
+    for action in config.keymap.actions() {
+        tracing::debug!(
+            name = action.name,
+            action = action.action,
+            accels = ?action.accels,
+            "Binding keybinds to action."
+        );
+
+        // This also clears any keybinds left over from before the config was reloaded.
+        app.set_accels_for_action(action.action, &action.accels);
     }
 
-    if let Some(key) = &config.keymap.select {
-        app.set_accels_for_action("win.select", &[key]);
-    }
-
-    if let Some(key) = &config.keymap.peek {
-        app.set_accels_for_action("win.peek", &[key]);
+    // There's a separate GTK action for each jump keybind, so clear the ones left over from
+    // before the config was reloaded first.
+    for action in app.list_action_descriptions() {
+        if action.starts_with("win.jump(") {
+            app.set_accels_for_action(&action, &[]);
+        }
     }
 
-    if let Some(key) = &config.keymap.next {
-        app.set_accels_for_action("win.next", &[key]);
+    for (action, keybind) in config.keymap.jump_actions() {
+        tracing::debug!(
+            action = %action,
+            accel = keybind.as_str(),
+            "Binding keybind to action."
+        );
+        app.set_accels_for_action(&action, &[keybind.as_str()]);
     }
+}
 
-    if let Some(key) = &config.keymap.prev {
-        app.set_accels_for_action("win.prev", &[key]);
-    }
+fn load_user_css(provider: &CssProvider) -> eyre::Result<()> {
+    let user_css = user_css_override().wrap_err("Failed to read the user's custom CSS.")?;
 
-    if let Some(key) = &config.keymap.peek_next {
-        app.set_accels_for_action("win.peek-next", &[key]);
-    }
+    // If the user removed their custom CSS file, this clears any styles they previously loaded.
+    provider.load_from_data(user_css.as_deref().unwrap_or_default());
 
-    if let Some(key) = &config.keymap.peek_prev {
-        app.set_accels_for_action("win.peek-prev", &[key]);
-    }
+    Ok(())
 }
 
-pub fn load_css() -> eyre::Result<()> {
+// Load the base CSS and the user's CSS overrides, returning the provider for the user's CSS
+// overrides so it can be reloaded later.
+fn load_css() -> eyre::Result<CssProvider> {
     // Load the base CSS.
     let app_provider = CssProvider::new();
     app_provider.load_from_data(include_str!("../style.css"));
 
     // Load the user's CSS overrides, if provided.
     let user_provider = CssProvider::new();
-    if let Some(user_css) = user_css_override().wrap_err("Failed to read the user's custom CSS.")? {
-        user_provider.load_from_data(&user_css);
-    }
+    user_provider.connect_parsing_error(|_, section, err| {
+        tracing::error!(
+            "Error in the user's custom CSS at {}: {}",
+            section.to_str(),
+            err
+        );
+    });
+    load_user_css(&user_provider)?;
 
     let display = Display::default().ok_or(eyre!("Could not connect to a display."))?;
 
@@ -227,29 +765,89 @@ pub fn load_css() -> eyre::Result<()> {
         gtk::STYLE_PROVIDER_PRIORITY_USER,
     );
 
+    Ok(user_provider)
+}
+
+// Apply the parts of the config that don't get read on demand.
+fn apply_config(config: &Config, app: &Application, subscription: &WindowSubscription) {
+    set_settings(config);
+    register_keybinds(config, app);
+    subscription.set_urgent_first(config.urgent_first);
+    subscription.set_frecency_half_life(Duration::from_secs(config.frecency_half_life));
+}
+
+fn register_config_watcher(
+    config_path: PathBuf,
+    config: SharedConfig,
+    app: &Application,
+    subscription: Rc<WindowSubscription>,
+    user_css: CssProvider,
+) -> eyre::Result<()> {
+    let css_path =
+        css_file_path().wrap_err("Failed to get the path of the user's custom CSS file.")?;
+    let receiver = watch::subscribe(&config_path, &css_path)?;
+
+    glib::spawn_future_local(clone!(@weak app => async move {
+        while let Ok(msg) = receiver.recv().await {
+            match msg {
+                Ok(ConfigFile::Config) => {
+                    // The file might have been deleted or moved away. We don't want to
+                    // regenerate the default config in that case.
+                    if !config_path.exists() {
+                        continue;
+                    }
+
+                    // Reading the config file could migrate it and write it back, which would
+                    // trigger another reload, so we only check it here.
+                    match Config::check(&config_path) {
+                        Ok(new_config) => {
+                            apply_config(&new_config, &app, &subscription);
+                            *config.borrow_mut() = new_config;
+                            tracing::info!("Reloaded the config file.");
+                        }
+                        Err(err) => tracing::error!("Failed reloading the config file. Keeping the previous config: {:?}", err),
+                    }
+                }
+                Ok(ConfigFile::Css) => match load_user_css(&user_css) {
+                    Ok(()) => tracing::info!("Reloaded the user's custom CSS."),
+                    Err(err) => tracing::error!("Failed reloading the user's custom CSS: {:?}", err),
+                },
+                Err(err) => tracing::error!("Error watching the config directory for changes: {:#}", err),
+            }
+        }
+
+        tracing::error!("Cannot receive next config change: Channel unexpectedly closed.");
+    }));
+
     Ok(())
 }
 
-pub fn build_window(config: &Config, app: &Application, subscription: Rc<WindowSubscription>) {
+pub fn build_window(
+    config: Config,
+    config_path: PathBuf,
+    app: &Application,
+    subscription: Rc<WindowSubscription>,
+) {
     let window = Window::new(app, WINDOW_TITLE);
 
-    set_settings(config);
+    let user_css = load_css().expect("Failed to load the GTK CSS.");
+
+    apply_config(&config, app, &subscription);
+
+    let config = Rc::new(RefCell::new(config));
 
     // Set this window up as an overlay via the Wayland Layer Shell protocol.
     window.init_layer_shell();
     window.set_layer(Layer::Overlay);
     window.set_keyboard_mode(KeyboardMode::None);
 
-    // Update the list of windows in the window switcher right before we display it.
-    let on_display = Box::new(clone!(@weak window => move || {
-        window.update_windows(&subscription.get_window_list().expect("Failed getting window list to populate window switcher overlay."));
-    }));
-
-    register_actions(&window, on_display);
-    register_keybinds(config, app);
-    register_key_release_controller(config, &window);
+    register_actions(&window, Rc::clone(&config), Rc::clone(&subscription));
+    register_key_release_controller(Rc::clone(&config), &window);
+    register_search_controller(&window);
     register_ipc_command_handlers(&window)
         .expect("Failed subscribing to IPC events from the swtchr client.");
+    register_config_watcher(config_path, config, app, subscription, user_css)
+        .expect("Failed watching the config directory for changes.");
 
     // The window is initially hidden until it receives the signal to display itself.
     window.present();
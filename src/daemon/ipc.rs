@@ -21,12 +21,18 @@ pub fn subscribe() -> eyre::Result<async_channel::Receiver<eyre::Result<Command>
     let socket =
         UnixDatagram::bind(&socket_path).wrap_err("Error binding to the swtchrd IPC socket.")?;
 
+    tracing::info!(path = %socket_path.display(), "Listening on the swtchrd IPC socket.");
+
     thread::spawn(move || {
         let mut buf = vec![0u8; Command::BUF_LEN];
 
         loop {
             let send_result = match socket.recv(&mut buf) {
-                Ok(num_bytes) => sender.send_blocking(Command::from_msg(&buf[..num_bytes])),
+                Ok(num_bytes) => {
+                    let command = Command::from_msg(&buf[..num_bytes]);
+                    tracing::debug!(?command, "Received IPC command from the swtchr client.");
+                    sender.send_blocking(command)
+                }
                 Err(err) => sender.send_blocking(Err(err.into())),
             };
 
@@ -35,7 +41,7 @@ pub fn subscribe() -> eyre::Result<async_channel::Receiver<eyre::Result<Command>
             }
         }
 
-        eprintln!("Cannot send next command: Channel unexpectedly closed.");
+        tracing::error!("Cannot send next command: Channel unexpectedly closed.");
     });
 
     Ok(receiver)
@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use gtk::gdk::{Key, ModifierType};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use toml::Spanned;
+
+use super::diagnostic::ConfigError;
+
+const ACCELERATOR_FORMAT_HELP: &str =
+    "Keybinds use the format documented here: https://docs.gtk.org/gtk4/func.accelerator_parse.html";
+
+// A keybind string, along with where it appears in the config file so we can point to it in
+// error messages.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "Spanned<String>")]
+pub struct Keybind {
+    key: String,
+    // Keybinds that don't come from the config file, like defaults, don't have a span.
+    span: Option<Range<usize>>,
+}
+
+// Where a keybind appears in the config file doesn't change what it does.
+impl PartialEq for Keybind {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl From<Spanned<String>> for Keybind {
+    fn from(spanned: Spanned<String>) -> Self {
+        Self {
+            span: Some(spanned.span()),
+            key: spanned.into_inner(),
+        }
+    }
+}
+
+// Parse a keybind into its key and modifiers the same way `gtk::accelerator_parse` does. We can't
+// use that here, because it needs GTK to be initialized, and checking the config file with
+// `swtchrd --check-config` shouldn't need a display.
+fn parse_accelerator(accel: &str) -> Option<(Key, ModifierType)> {
+    let mut modifiers = ModifierType::empty();
+    let mut rest = accel;
+
+    while let Some(after_bracket) = rest.strip_prefix('<') {
+        let (name, after_modifier) = after_bracket.split_once('>')?;
+
+        modifiers |= match name.to_ascii_lowercase().as_str() {
+            "shift" | "shft" => ModifierType::SHIFT_MASK,
+            "control" | "ctrl" | "ctl" | "primary" => ModifierType::CONTROL_MASK,
+            "alt" | "mod1" => ModifierType::ALT_MASK,
+            "super" => ModifierType::SUPER_MASK,
+            "hyper" => ModifierType::HYPER_MASK,
+            "meta" => ModifierType::META_MASK,
+            _ => return None,
+        };
+
+        rest = after_modifier;
+    }
+
+    // GTK treats `<Super>P` and `<Super>p` as the same keybind.
+    Key::from_name(rest).map(|key| (key.to_lower(), modifiers))
+}
+
+impl Keybind {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_owned(),
+            span: None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+
+    fn error(&self, message: String) -> ConfigError {
+        let err = ConfigError::new(message);
+
+        match &self.span {
+            Some(span) => err.with_span(span.clone()),
+            None => err,
+        }
+    }
+
+    pub fn parse(&self) -> Option<(Key, ModifierType)> {
+        parse_accelerator(self.as_str())
+    }
+
+    // Whether this keybind comes from the config file rather than from the defaults.
+    fn is_explicit(&self) -> bool {
+        self.span.is_some()
+    }
+
+    pub fn validate(&self, name: &str) -> Result<(), ConfigError> {
+        if self.parse().is_none() {
+            return Err(self
+                .error(format!(
+                    "Invalid keybind for `{}`: `{}`",
+                    name,
+                    self.as_str()
+                ))
+                .with_help(ACCELERATOR_FORMAT_HELP));
+        }
+
+        Ok(())
+    }
+}
+
+struct KeybindsVisitor;
+
+impl<'de> Visitor<'de> for KeybindsVisitor {
+    type Value = Vec<Keybind>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a keybind or a list of keybinds")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(vec![Keybind::new(value)])
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut keybinds = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+
+        while let Some(keybind) = seq.next_element::<Keybind>()? {
+            keybinds.push(keybind);
+        }
+
+        Ok(keybinds)
+    }
+}
+
+struct KeybindList(Vec<Keybind>);
+
+impl<'de> Deserialize<'de> for KeybindList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(KeybindsVisitor)
+            .map(KeybindList)
+    }
+}
+
+// The keybinds for an action. In the config file, this can either be a single keybind or a list
+// of them. An empty list leaves the action unbound.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Keybinds(Vec<Keybind>);
+
+impl<'de> Deserialize<'de> for Keybinds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let spanned = Spanned::<KeybindList>::deserialize(deserializer)?;
+        let span = spanned.span();
+
+        // A keybind given as a single string doesn't have a span of its own, so point to the
+        // whole value instead.
+        let keybinds = spanned
+            .into_inner()
+            .0
+            .into_iter()
+            .map(|keybind| Keybind {
+                span: keybind.span.or_else(|| Some(span.clone())),
+                ..keybind
+            })
+            .collect();
+
+        Ok(Self(keybinds))
+    }
+}
+
+impl Keybinds {
+    fn new(keys: &[&str]) -> Self {
+        Self(keys.iter().copied().map(Keybind::new).collect())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Keybind> {
+        self.0.iter()
+    }
+}
+
+// Any actions missing from the `[keymap]` table use their default keybinds.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeymapConfig {
+    pub dismiss: Keybinds,
+    pub select: Keybinds,
+    pub peek: Keybinds,
+    pub next: Keybinds,
+    pub prev: Keybinds,
+    pub peek_next: Keybinds,
+    pub peek_prev: Keybinds,
+    pub expand: Keybinds,
+    pub pin: Keybinds,
+    // Unlike the other actions, each of these keybinds does something different: the first one
+    // selects the first window, the second one selects the second window, and so on.
+    pub jump: Keybinds,
+}
+
+impl Default for KeymapConfig {
+    fn default() -> Self {
+        Self {
+            dismiss: Keybinds::new(&["Escape"]),
+            select: Keybinds::default(),
+            peek: Keybinds::default(),
+            next: Keybinds::new(&["<Super>Tab"]),
+            prev: Keybinds::new(&["<Super><Shift>Tab"]),
+            peek_next: Keybinds::default(),
+            peek_prev: Keybinds::default(),
+            expand: Keybinds::new(&["<Super>grave"]),
+            pin: Keybinds::new(&["<Super>p"]),
+            jump: Keybinds::new(&[
+                "<Super>1", "<Super>2", "<Super>3", "<Super>4", "<Super>5", "<Super>6", "<Super>7",
+                "<Super>8", "<Super>9",
+            ]),
+        }
+    }
+}
+
+// An action that can be bound in the keymap.
+#[derive(Debug, Clone)]
+pub struct KeymapAction<'a> {
+    // The name of the action in the config file.
+    pub name: &'static str,
+
+    // The name of the GTK action.
+    pub action: &'static str,
+
+    // The keybinds for the action, not including any default keybinds that the user bound to
+    // other actions.
+    pub accels: Vec<&'a str>,
+}
+
+impl KeymapConfig {
+    // Every action, along with the name of its GTK action and its keybinds.
+    fn bindings(&self) -> [(&'static str, &'static str, &Keybinds); 9] {
+        [
+            ("dismiss", "win.dismiss", &self.dismiss),
+            ("select", "win.select", &self.select),
+            ("peek", "win.peek", &self.peek),
+            ("next", "win.next", &self.next),
+            ("prev", "win.prev", &self.prev),
+            ("peek_next", "win.peek-next", &self.peek_next),
+            ("peek_prev", "win.peek-prev", &self.peek_prev),
+            ("expand", "win.expand", &self.expand),
+            ("pin", "win.pin", &self.pin),
+        ]
+    }
+
+    // Every keybind in the keymap, along with the name of its action.
+    fn keybinds(&self) -> impl Iterator<Item = (&'static str, &Keybind)> {
+        self.bindings()
+            .into_iter()
+            .map(|(name, _, keybinds)| (name, keybinds))
+            .chain([("jump", &self.jump)])
+            .flat_map(|(name, keybinds)| keybinds.iter().map(move |keybind| (name, keybind)))
+    }
+
+    // If this is a default keybind that the user bound to another action in the config file,
+    // return the name of that action. The user's keybind wins, so that adding a default keybind
+    // for a new action doesn't break config files that already use that keybind.
+    fn shadowed_by(&self, keybind: &Keybind) -> Option<&'static str> {
+        if keybind.is_explicit() {
+            return None;
+        }
+
+        self.keybinds()
+            .find(|(_, other)| other.is_explicit() && other.parse() == keybind.parse())
+            .map(|(name, _)| name)
+    }
+
+    // The GTK actions for the `jump` keybinds, along with the keybind for each.
+    pub fn jump_actions(&self) -> Vec<(String, &Keybind)> {
+        self.jump
+            .iter()
+            .enumerate()
+            .filter(|(_, keybind)| self.shadowed_by(keybind).is_none())
+            .map(|(index, keybind)| (format!("win.jump({})", index + 1), keybind))
+            .collect()
+    }
+
+    pub fn actions(&self) -> Vec<KeymapAction<'_>> {
+        self.bindings()
+            .into_iter()
+            .map(|(name, action, keybinds)| KeymapAction {
+                name,
+                action,
+                accels: keybinds
+                    .iter()
+                    .filter(|keybind| self.shadowed_by(keybind).is_none())
+                    .map(Keybind::as_str)
+                    .collect(),
+            })
+            .collect()
+    }
+
+    // Check that every keybind is valid, and that no two actions have the same keybind. Default
+    // keybinds that the user bound to another action are ignored with a warning, so only keybinds
+    // from the config file can conflict.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (name, keybind) in self.keybinds() {
+            keybind.validate(name)?;
+        }
+
+        // Keybinds are compared by their parsed key and modifiers, so that `<Ctrl>q` and
+        // `<Control>q` count as the same keybind.
+        let mut bound = HashMap::new();
+
+        for (name, keybind) in self.keybinds() {
+            if let Some(other_name) = self.shadowed_by(keybind) {
+                tracing::warn!(
+                    "The default keybind `{}` for `{}` is unbound, because the config file binds it to `{}`.",
+                    keybind.as_str(),
+                    name,
+                    other_name,
+                );
+                continue;
+            }
+
+            match bound.get(&keybind.parse()) {
+                Some(other_name) if *other_name != name => {
+                    return Err(keybind
+                        .error(format!(
+                            "The keybind `{}` for `{}` is already bound to `{}`.",
+                            keybind.as_str(),
+                            name,
+                            other_name
+                        ))
+                        .with_help(format!(
+                            "Remove it from one of the actions, or use `{} = []` to unbind `{}`.",
+                            other_name, other_name
+                        )));
+                }
+                _ => {
+                    bound.insert(keybind.parse(), name);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The keybinds that will be bound to the action, for tests.
+#[cfg(test)]
+pub(crate) fn accels<'a>(keymap: &'a KeymapConfig, name: &str) -> Vec<&'a str> {
+    keymap
+        .actions()
+        .into_iter()
+        .find(|action| action.name == name)
+        .unwrap()
+        .accels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(contents: &str) -> KeymapConfig {
+        toml::from_str(contents).unwrap()
+    }
+
+    #[test]
+    fn parse_accelerator_with_modifiers() {
+        assert_eq!(
+            parse_accelerator("<Super><Shift>Tab"),
+            Some((
+                Key::Tab,
+                ModifierType::SUPER_MASK | ModifierType::SHIFT_MASK
+            ))
+        );
+        assert_eq!(
+            parse_accelerator("<ctrl>q"),
+            Some((Key::q, ModifierType::CONTROL_MASK))
+        );
+        assert_eq!(
+            parse_accelerator("Escape"),
+            Some((Key::Escape, ModifierType::empty()))
+        );
+    }
+
+    #[test]
+    fn parse_accelerator_ignores_case_of_key() {
+        assert_eq!(parse_accelerator("<Super>P"), parse_accelerator("<Super>p"));
+    }
+
+    #[test]
+    fn parse_invalid_accelerator() {
+        assert_eq!(parse_accelerator("<Supr>p"), None);
+        assert_eq!(parse_accelerator("<Super>"), None);
+        assert_eq!(parse_accelerator("<Super"), None);
+        assert_eq!(parse_accelerator("NotAKey"), None);
+    }
+
+    #[test]
+    fn default_keymap_is_valid() {
+        assert!(KeymapConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_keybind() {
+        let err = keymap("select = \"<Supr>p\"").validate().unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("Invalid keybind for `select`: `<Supr>p`"));
+    }
+
+    #[test]
+    fn conflicting_keybinds() {
+        let err = keymap("select = \"<Super>Return\"\npeek = \"<Super>Return\"")
+            .validate()
+            .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("The keybind `<Super>Return` for `peek` is already bound to `select`."));
+    }
+
+    #[test]
+    fn conflicting_keybinds_with_different_spelling() {
+        assert!(keymap("select = \"<Ctrl>q\"\npeek = \"<Control>Q\"")
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn same_keybind_twice_for_one_action() {
+        assert!(keymap("select = [\"<Super>Return\", \"<Super>Return\"]")
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn explicit_keybind_shadows_default() {
+        let keymap = keymap("select = \"Escape\"");
+
+        assert!(keymap.validate().is_ok());
+        assert_eq!(accels(&keymap, "select"), ["Escape"]);
+        assert!(accels(&keymap, "dismiss").is_empty());
+    }
+
+    #[test]
+    fn explicit_keybind_shadows_default_in_list() {
+        let keymap = keymap("next = [\"<Super>Tab\", \"<Super>grave\"]");
+
+        assert!(keymap.validate().is_ok());
+        assert!(accels(&keymap, "expand").is_empty());
+    }
+
+    #[test]
+    fn explicit_keybind_shadows_default_jump() {
+        let keymap = keymap("next = \"<Super>1\"");
+        let jump_actions = keymap.jump_actions();
+
+        assert!(keymap.validate().is_ok());
+        assert_eq!(jump_actions.len(), 8);
+        assert_eq!(jump_actions[0].0, "win.jump(2)");
+        assert_eq!(jump_actions[0].1.as_str(), "<Super>2");
+    }
+
+    #[test]
+    fn explicit_keybinds_conflict_with_each_other() {
+        assert!(keymap("select = \"Escape\"\ndismiss = \"Escape\"")
+            .validate()
+            .is_err());
+    }
+}
@@ -1,11 +1,20 @@
 mod cli;
 mod components;
 mod config;
+mod diagnostic;
 mod gui;
 mod ipc;
+mod keymap;
+mod migrate;
+mod release;
+mod rules;
+mod search;
+mod view;
+mod watch;
 
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 
 use clap::Parser;
 use eyre::{bail, WrapErr};
@@ -15,7 +24,8 @@ use gtk::Application;
 
 use cli::Cli;
 use config::{config_file_path, Config};
-use gui::{build_window, load_css};
+use gui::build_window;
+use swtchr::logging;
 use swtchr::sway::{check_is_sway_session, WindowSubscription};
 
 pub const APP_ID: &str = "io.github.lostatc.swtchr";
@@ -24,31 +34,53 @@ pub const WINDOW_TITLE: &str = "swtchr";
 fn main() -> eyre::Result<()> {
     color_eyre::install()?;
 
-    gtk::init().wrap_err("Failed to initialize the GTK runtime.")?;
-
     let args = Cli::parse();
 
+    logging::init(&args.log, "swtchrd")?;
+
     let config_path = match &args.config {
         Some(path) => PathBuf::from(path),
         None => config_file_path().wrap_err("Failed getting the config file path.")?,
     };
 
+    if args.check_config {
+        Config::check(&config_path).wrap_err("Failed checking the swtchr.toml config file.")?;
+        println!("The config file is valid: {}", config_path.display());
+        return Ok(());
+    }
+
+    // Checking the config file doesn't need a display, so do it before starting GTK.
+    gtk::init().wrap_err("Failed to initialize the GTK runtime.")?;
+
+    tracing::info!(path = %config_path.display(), "Reading the config file.");
+
     let config =
         Config::read(&config_path).wrap_err("Failed reading the swtchr.toml config file.")?;
 
+    tracing::debug!(?config, "Loaded the config file.");
+
     if !args.no_check {
         check_is_sway_session()?;
     }
 
     let subscription = Rc::new(
-        WindowSubscription::subscribe(config.urgent_first)
-            .wrap_err("Failed subscribing to Sway window focus events.")?,
+        WindowSubscription::subscribe(
+            config.urgent_first,
+            Duration::from_secs(config.frecency_half_life),
+        )
+        .wrap_err("Failed subscribing to Sway window focus events.")?,
     );
 
     let app = Application::builder().application_id(APP_ID).build();
 
-    app.connect_startup(|_| load_css().expect("Failed to load the GTK CSS."));
-    app.connect_activate(move |app| build_window(&config, app, Rc::clone(&subscription)));
+    app.connect_activate(move |app| {
+        build_window(
+            config.clone(),
+            config_path.clone(),
+            app,
+            Rc::clone(&subscription),
+        )
+    });
 
     // GTK attempts to do its own CLI argument parsing. We need to override that to avoid it
     // erroring on CLI args it doesn't recognize.
@@ -0,0 +1,391 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use eyre::{eyre, WrapErr};
+
+// The current version of the config file format. Bump this and add a migration to `MIGRATIONS`
+// whenever the format changes in a way that existing config files need to be updated for.
+pub const CONFIG_VERSION: u32 = 1;
+
+// Each migration upgrades a config file from the version at its index to the next version.
+const MIGRATIONS: [fn(&str) -> String; CONFIG_VERSION as usize] = [migrate_v0_to_v1];
+
+// The keymap actions that existed before the config format was versioned and now have default
+// keybinds.
+const DEFAULT_BOUND_ACTIONS: [&str; 3] = ["dismiss", "next", "prev"];
+
+// The options that every config file had to set before the format was versioned.
+const V0_REQUIRED_OPTIONS: [&str; 5] = [
+    "urgent_first",
+    "dismiss_on_release",
+    "select_on_release",
+    "release_keys",
+    "keymap",
+];
+
+const UNBIND_COMMENT: &str = "\
+# Added by swtchr when updating this config file. Actions you leave out of the
+# keymap now use their default keybinds, so these keep them unbound like before.
+";
+
+const VERSION_COMMENT: &str = "\
+# The version of the config file format. swtchr uses this to update your config
+# file automatically when the format changes. You shouldn't change this.
+";
+
+// Return the byte offset of the start of each line, along with the line with surrounding
+// whitespace removed.
+fn line_offsets(contents: &str) -> impl Iterator<Item = (usize, &str)> {
+    contents.split_inclusive('\n').scan(0, |offset, line| {
+        let line_start = *offset;
+        *offset += line.len();
+        Some((line_start, line.trim()))
+    })
+}
+
+// Add a top-level `version` key before the first option in the file, so that it ends up at the
+// top level instead of inside a table.
+fn add_version(contents: &str, version: u32) -> String {
+    let insert_at = line_offsets(contents)
+        .find(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map_or(contents.len(), |(line_start, _)| line_start);
+
+    let mut migrated = String::with_capacity(contents.len() + VERSION_COMMENT.len() * 2);
+    migrated.push_str(&contents[..insert_at]);
+
+    if !migrated.is_empty() && !migrated.ends_with('\n') {
+        migrated.push('\n');
+    }
+
+    migrated.push_str(VERSION_COMMENT);
+    migrated.push_str(&format!("version = {}\n\n", version));
+    migrated.push_str(&contents[insert_at..]);
+
+    migrated
+}
+
+// Actions left out of the `[keymap]` table used to be unbound. Now they use their default
+// keybinds, so explicitly unbind any actions that were left out.
+fn unbind_missing_actions(contents: &str) -> String {
+    let keymap = match contents.parse::<toml::Table>().ok().and_then(|table| {
+        table
+            .get("keymap")
+            .and_then(|keymap| keymap.as_table().cloned())
+    }) {
+        Some(keymap) => keymap,
+        None => return contents.to_owned(),
+    };
+
+    let unbound = DEFAULT_BOUND_ACTIONS
+        .iter()
+        .filter(|action| !keymap.contains_key(**action))
+        .collect::<Vec<_>>();
+
+    if unbound.is_empty() {
+        return contents.to_owned();
+    }
+
+    let header_end = line_offsets(contents)
+        .find(|(_, line)| line.split('#').next().unwrap_or_default().replace(' ', "") == "[keymap]")
+        .map(|(line_start, _)| {
+            contents[line_start..]
+                .find('\n')
+                .map_or(contents.len(), |i| line_start + i + 1)
+        });
+
+    let insert_at = match header_end {
+        Some(insert_at) => insert_at,
+        None => {
+            // The keymap is written as an inline table or with dotted keys, which we don't try
+            // to edit.
+            tracing::warn!(
+                actions = ?unbound,
+                "Could not find the `[keymap]` table to update. These actions will now use their default keybinds."
+            );
+            return contents.to_owned();
+        }
+    };
+
+    let mut migrated = String::with_capacity(contents.len() + UNBIND_COMMENT.len() * 2);
+    migrated.push_str(&contents[..insert_at]);
+
+    if !migrated.ends_with('\n') {
+        migrated.push('\n');
+    }
+
+    migrated.push('\n');
+    migrated.push_str(UNBIND_COMMENT);
+
+    for action in unbound {
+        migrated.push_str(&format!("{} = []\n", action));
+    }
+
+    migrated.push_str(&contents[insert_at..]);
+
+    migrated
+}
+
+// Config files from before the format was versioned don't have a `version` key, and every option
+// was required. Actions left out of the `[keymap]` table were unbound.
+fn migrate_v0_to_v1(contents: &str) -> String {
+    unbind_missing_actions(&add_version(contents, 1))
+}
+
+// Return the version of the config file format, or `None` if the file can't be parsed.
+//
+// Files from before the format was versioned are version 0. Since every option is optional now,
+// a file without a `version` is only one of those if it sets all the options they required.
+// Otherwise, it's a new config file that just leaves out `version`, and we shouldn't touch it.
+fn config_version(contents: &str) -> Option<u32> {
+    let table = contents.parse::<toml::Table>().ok()?;
+
+    match table.get("version") {
+        Some(version) => version.as_integer().and_then(|v| u32::try_from(v).ok()),
+        None if V0_REQUIRED_OPTIONS
+            .iter()
+            .all(|option| table.contains_key(*option)) =>
+        {
+            Some(0)
+        }
+        None => Some(CONFIG_VERSION),
+    }
+}
+
+// Upgrade the contents of a config file to the current version of the config format, returning
+// `None` if it's already the current version.
+//
+// If the file isn't valid, we leave it alone and let the caller report the error.
+pub fn migrate(contents: &str) -> Option<String> {
+    let version = match config_version(contents) {
+        Some(version) if version < CONFIG_VERSION => version,
+        _ => return None,
+    };
+
+    Some(
+        MIGRATIONS[version as usize..]
+            .iter()
+            .fold(contents.to_owned(), |contents, migration| {
+                migration(&contents)
+            }),
+    )
+}
+
+// Replace the config file with the migrated one, backing up the original alongside it.
+//
+// If the config file is a symlink, like when it's managed by a dotfile manager, we update the file
+// it points to instead of replacing the symlink.
+pub fn save_migrated(path: &Path, original: &str, migrated: &str) -> eyre::Result<()> {
+    let path = fs::canonicalize(path).wrap_err("Failed resolving the path of the config file.")?;
+
+    let file_name = path.file_name().ok_or(eyre!(
+        "The config file path does not have a file name. This is a bug."
+    ))?;
+    let mut backup_name = file_name.to_owned();
+    backup_name.push(".bak");
+    let backup_path = path.with_file_name(backup_name);
+
+    fs::write(&backup_path, original)
+        .wrap_err("Failed backing up the config file before migrating it.")?;
+
+    // Write the migrated config to a temporary file and rename it over the original, so the
+    // config file is never left half-written.
+    let mut tmp_name = file_name.to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut tmp_file =
+        fs::File::create(&tmp_path).wrap_err("Failed creating a temporary config file.")?;
+    tmp_file
+        .write_all(migrated.as_bytes())
+        .wrap_err("Failed writing the migrated config file.")?;
+    fs::rename(&tmp_path, &path)
+        .wrap_err("Failed replacing the config file with the migrated one.")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::os::unix::fs::symlink;
+    use std::path::PathBuf;
+    use std::process;
+
+    use super::*;
+
+    const V0_CONFIG: &str = "\
+# A config file from before the format was versioned.
+
+urgent_first = true
+dismiss_on_release = true
+select_on_release = true
+release_keys = [\"Super_L\"]
+
+[keymap]
+dismiss = \"Escape\"
+next = \"<Super>Tab\"
+prev = \"<Super><Shift>Tab\"
+";
+
+    // A directory to write config files to for a single test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("swtchr-test-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn add_version_after_leading_comments() {
+        assert_eq!(
+            add_version("# Comment\n\nurgent_first = true\n", 1),
+            format!(
+                "# Comment\n\n{}version = 1\n\nurgent_first = true\n",
+                VERSION_COMMENT
+            )
+        );
+    }
+
+    #[test]
+    fn add_version_before_first_table() {
+        assert_eq!(
+            add_version("[keymap]\nnext = \"<Super>Tab\"\n", 1),
+            format!(
+                "{}version = 1\n\n[keymap]\nnext = \"<Super>Tab\"\n",
+                VERSION_COMMENT
+            )
+        );
+    }
+
+    #[test]
+    fn add_version_to_file_with_only_comments() {
+        assert_eq!(
+            add_version("# Comment", 1),
+            format!("# Comment\n{}version = 1\n\n", VERSION_COMMENT)
+        );
+        assert_eq!(
+            add_version("", 1),
+            format!("{}version = 1\n\n", VERSION_COMMENT)
+        );
+    }
+
+    #[test]
+    fn unbind_missing_actions_in_keymap() {
+        let contents = "[keymap] # Keybinds\ndismiss = \"Escape\"\n";
+
+        assert_eq!(
+            unbind_missing_actions(contents),
+            format!(
+                "[keymap] # Keybinds\n\n{}next = []\nprev = []\ndismiss = \"Escape\"\n",
+                UNBIND_COMMENT
+            )
+        );
+    }
+
+    #[test]
+    fn unbind_missing_actions_keeps_complete_keymap() {
+        let contents =
+            "[keymap]\ndismiss = []\nnext = \"<Super>Tab\"\nprev = \"<Super><Shift>Tab\"\n";
+
+        assert_eq!(unbind_missing_actions(contents), contents);
+    }
+
+    #[test]
+    fn unbind_missing_actions_without_keymap() {
+        let contents = "urgent_first = true\n";
+
+        assert_eq!(unbind_missing_actions(contents), contents);
+    }
+
+    #[test]
+    fn unbind_missing_actions_leaves_inline_keymap_alone() {
+        let contents = "keymap = { dismiss = \"Escape\" }\n";
+
+        assert_eq!(unbind_missing_actions(contents), contents);
+    }
+
+    #[test]
+    fn migrate_v0_to_v1_adds_version_and_unbinds_actions() {
+        let contents = "urgent_first = true\n\n[keymap]\nnext = \"<Super>Tab\"\n";
+
+        assert_eq!(
+            migrate_v0_to_v1(contents),
+            format!(
+                "{}version = 1\n\nurgent_first = true\n\n[keymap]\n\n{}dismiss = []\nprev = []\nnext = \"<Super>Tab\"\n",
+                VERSION_COMMENT, UNBIND_COMMENT
+            )
+        );
+    }
+
+    #[test]
+    fn config_version_of_file() {
+        assert_eq!(config_version(V0_CONFIG), Some(0));
+        assert_eq!(config_version("version = 1\n"), Some(1));
+        assert_eq!(config_version("version = -1\n"), None);
+        assert_eq!(config_version("version = \n"), None);
+    }
+
+    #[test]
+    fn config_version_of_new_file_without_version() {
+        assert_eq!(config_version(""), Some(CONFIG_VERSION));
+        assert_eq!(
+            config_version("urgent_first = true\n\n[keymap]\nnext = \"<Super>Tab\"\n"),
+            Some(CONFIG_VERSION)
+        );
+        assert_eq!(migrate("[keymap]\nnext = \"<Super>Tab\"\n"), None);
+    }
+
+    #[test]
+    fn migrate_only_old_versions() {
+        assert_eq!(migrate(&format!("version = {}\n", CONFIG_VERSION)), None);
+        assert_eq!(
+            migrate(&format!("version = {}\n", CONFIG_VERSION + 1)),
+            None
+        );
+        assert_eq!(migrate("not toml"), None);
+
+        let migrated = migrate(V0_CONFIG).unwrap();
+
+        assert_eq!(config_version(&migrated), Some(CONFIG_VERSION));
+        assert_eq!(migrate(&migrated), None);
+    }
+
+    #[test]
+    fn save_migrated_backs_up_original() {
+        let dir = test_dir("backup");
+        let path = dir.join("swtchr.toml");
+        fs::write(&path, V0_CONFIG).unwrap();
+
+        save_migrated(&path, V0_CONFIG, "version = 1\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "version = 1\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("swtchr.toml.bak")).unwrap(),
+            V0_CONFIG
+        );
+        assert!(!dir.join("swtchr.toml.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_migrated_keeps_symlink() {
+        let dir = test_dir("symlink");
+        let target = dir.join("dotfiles.toml");
+        let path = dir.join("swtchr.toml");
+        fs::write(&target, V0_CONFIG).unwrap();
+        symlink(&target, &path).unwrap();
+
+        save_migrated(&path, V0_CONFIG, "version = 1\n").unwrap();
+
+        assert!(fs::symlink_metadata(&path).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "version = 1\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("dotfiles.toml.bak")).unwrap(),
+            V0_CONFIG
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+use gtk::gdk::{self, Display, Key, ModifierType};
+use gtk::prelude::*;
+
+use super::config::Config;
+use super::keymap::Keybind;
+
+// Return the modifier that a modifier key activates, if it is one.
+fn key_modifier(key: Key) -> ModifierType {
+    match key {
+        Key::Super_L | Key::Super_R => ModifierType::SUPER_MASK,
+        Key::Hyper_L | Key::Hyper_R => ModifierType::HYPER_MASK,
+        Key::Meta_L | Key::Meta_R => ModifierType::META_MASK,
+        Key::Alt_L | Key::Alt_R => ModifierType::ALT_MASK,
+        Key::Control_L | Key::Control_R => ModifierType::CONTROL_MASK,
+        Key::Shift_L | Key::Shift_R => ModifierType::SHIFT_MASK,
+        _ => ModifierType::empty(),
+    }
+}
+
+// The keys that trigger `dismiss_on_release` and `select_on_release`.
+//
+// Matching on the released key alone isn't reliable. On non-US layouts, and when users remap
+// their modifier keys, the key that gets released might not be the one in `release_keys`. So we
+// also match on the hardware keycodes those keys are currently mapped to, and on the modifier
+// state, which is the same no matter which physical key activates the modifier.
+#[derive(Debug)]
+pub struct ReleaseKeys {
+    keys: HashSet<Key>,
+    keycodes: HashSet<u32>,
+    modifiers: ModifierType,
+}
+
+impl ReleaseKeys {
+    pub fn new(config: &Config, display: &Display) -> Self {
+        let mut release_keys = Self {
+            keys: HashSet::new(),
+            keycodes: HashSet::new(),
+            modifiers: ModifierType::empty(),
+        };
+
+        for (key, modifiers) in config.release_keys.iter().filter_map(Keybind::parse) {
+            release_keys.keys.insert(key);
+            release_keys.modifiers |= modifiers | key_modifier(key);
+
+            if let Some(keymap_keys) = display.map_keyval(key) {
+                release_keys
+                    .keycodes
+                    .extend(keymap_keys.iter().map(gdk::KeymapKey::keycode));
+            }
+        }
+
+        // Use the modifiers held to cycle through windows, like `<Super>` in `<Super>Tab`, as
+        // release keys.
+        if config.auto_release_keys {
+            for (_, modifiers) in config
+                .keymap
+                .next
+                .iter()
+                .chain(config.keymap.prev.iter())
+                .filter_map(Keybind::parse)
+            {
+                // Shift is usually only held to reverse the direction, like in `<Super><Shift>Tab`,
+                // so releasing it shouldn't select a window.
+                release_keys.modifiers |= modifiers - ModifierType::SHIFT_MASK;
+            }
+        }
+
+        release_keys
+    }
+
+    // Whether releasing this key should trigger the release actions.
+    pub fn is_release_key(&self, key: Key, keycode: u32) -> bool {
+        self.keys.contains(&key) || self.keycodes.contains(&keycode)
+    }
+
+    // Whether all the release modifiers are up in the given modifier state.
+    pub fn is_released(&self, state: ModifierType) -> bool {
+        !self.modifiers.is_empty() && !state.intersects(self.modifiers)
+    }
+}
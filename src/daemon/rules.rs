@@ -0,0 +1,269 @@
+use std::ops::Range;
+
+use regex::Regex;
+use serde::Deserialize;
+use toml::Spanned;
+
+use super::diagnostic::ConfigError;
+use swtchr::sway::Window;
+
+// What to do with the windows a rule matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    // Leave the window out of the window switcher.
+    #[default]
+    Hide,
+
+    // Show the window after all the other windows.
+    Last,
+}
+
+// How the patterns in a rule are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatternSyntax {
+    // Regular expressions, which match anywhere in the string unless anchored, like Sway's
+    // criteria.
+    #[default]
+    Regex,
+
+    // Shell-style globs, which must match the whole string.
+    Glob,
+}
+
+// A matcher as it's written in the config file, before its patterns are compiled. Rules are
+// written the same way, plus an `action`.
+//
+// We can't share the fields between rules and matchers with `#[serde(flatten)]`, because the
+// patterns wouldn't keep their spans.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMatcher {
+    action: Option<Spanned<RuleAction>>,
+    #[serde(default)]
+    syntax: PatternSyntax,
+    app_id: Option<Spanned<String>>,
+    class: Option<Spanned<String>>,
+    instance: Option<Spanned<String>>,
+    title: Option<Spanned<String>>,
+    workspace: Option<Spanned<String>>,
+    output: Option<Spanned<String>>,
+    floating: Option<bool>,
+}
+
+// Translate a glob into an equivalent regex. We only support `*` and `?`.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+
+    for glob_char in glob.chars() {
+        match glob_char {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            _ => regex.push_str(&regex::escape(glob_char.encode_utf8(&mut [0; 4]))),
+        }
+    }
+
+    regex.push('$');
+
+    regex
+}
+
+// A pattern from the config file, along with where it appears in the config file so we can point
+// to it if it's not a valid regex.
+#[derive(Debug, Clone)]
+struct Pattern {
+    key: &'static str,
+    span: Range<usize>,
+    regex: Result<Regex, regex::Error>,
+}
+
+impl Pattern {
+    fn compile(
+        key: &'static str,
+        pattern: Option<Spanned<String>>,
+        syntax: PatternSyntax,
+    ) -> Option<Self> {
+        let pattern = pattern?;
+
+        let regex = match syntax {
+            PatternSyntax::Regex => Regex::new(pattern.get_ref()),
+            PatternSyntax::Glob => Regex::new(&glob_to_regex(pattern.get_ref())),
+        };
+
+        Some(Self {
+            key,
+            span: pattern.span(),
+            regex,
+        })
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        self.regex.as_ref().is_ok_and(|regex| regex.is_match(value))
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        match &self.regex {
+            Ok(_) => Ok(()),
+            Err(err) => Err(ConfigError::new(format!(
+                "Invalid pattern for `{}`: {}",
+                self.key, err
+            ))
+            .with_span(self.span.clone())),
+        }
+    }
+}
+
+// Regexes can't be compared, so compare the patterns they were compiled from instead.
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        let same_regex = match (&self.regex, &other.regex) {
+            (Ok(regex), Ok(other_regex)) => regex.as_str() == other_regex.as_str(),
+            (Err(err), Err(other_err)) => err == other_err,
+            _ => false,
+        };
+
+        self.key == other.key && same_regex
+    }
+}
+
+// Matches windows by their properties. A matcher matches a window when all of the properties it
+// specifies match. A property the window doesn't have, like the X11 class of a Wayland window,
+// never matches.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "RawMatcher")]
+pub struct Matcher {
+    app_id: Option<Pattern>,
+    class: Option<Pattern>,
+    instance: Option<Pattern>,
+    title: Option<Pattern>,
+    workspace: Option<Pattern>,
+    output: Option<Pattern>,
+    floating: Option<bool>,
+    // Where the `action` is in the config file, if this matcher has one even though it isn't part
+    // of a rule.
+    action_span: Option<Range<usize>>,
+}
+
+impl From<RawMatcher> for Matcher {
+    fn from(raw: RawMatcher) -> Self {
+        Self {
+            app_id: Pattern::compile("app_id", raw.app_id, raw.syntax),
+            class: Pattern::compile("class", raw.class, raw.syntax),
+            instance: Pattern::compile("instance", raw.instance, raw.syntax),
+            title: Pattern::compile("title", raw.title, raw.syntax),
+            workspace: Pattern::compile("workspace", raw.workspace, raw.syntax),
+            output: Pattern::compile("output", raw.output, raw.syntax),
+            floating: raw.floating,
+            action_span: raw.action.map(|action| action.span()),
+        }
+    }
+}
+
+// A matcher, along with what to do with the windows it matches.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "RawMatcher")]
+pub struct Rule {
+    pub action: RuleAction,
+    matcher: Matcher,
+}
+
+impl From<RawMatcher> for Rule {
+    fn from(mut raw: RawMatcher) -> Self {
+        Self {
+            action: raw
+                .action
+                .take()
+                .map(Spanned::into_inner)
+                .unwrap_or_default(),
+            matcher: Matcher::from(raw),
+        }
+    }
+}
+
+fn matches(pattern: &Option<Pattern>, value: Option<&str>) -> bool {
+    match (pattern, value) {
+        (None, _) => true,
+        (Some(pattern), Some(value)) => pattern.is_match(value),
+        (Some(_), None) => false,
+    }
+}
+
+impl Matcher {
+    pub fn matches(&self, window: &Window) -> bool {
+        matches(&self.app_id, window.app_id.as_deref())
+            && matches(&self.class, window.class.as_deref())
+            && matches(&self.instance, window.instance.as_deref())
+            && matches(&self.title, Some(&window.title))
+            && matches(&self.workspace, window.workspace.as_deref())
+            && matches(&self.output, window.output.as_deref())
+            && self
+                .floating
+                .is_none_or(|floating| floating == window.floating)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(span) = &self.action_span {
+            return Err(
+                ConfigError::new("Only rules can have an `action`.").with_span(span.clone())
+            );
+        }
+
+        [
+            &self.app_id,
+            &self.class,
+            &self.instance,
+            &self.title,
+            &self.workspace,
+            &self.output,
+        ]
+        .into_iter()
+        .flatten()
+        .try_for_each(Pattern::validate)
+    }
+}
+
+impl Rule {
+    pub fn matches(&self, window: &Window) -> bool {
+        self.matcher.matches(window)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.matcher.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob_matches(glob: &str, text: &str) -> bool {
+        Regex::new(&glob_to_regex(glob)).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn glob_star_matches_any_characters() {
+        assert_eq!(glob_to_regex("*music*"), "^.*music.*$");
+        assert!(glob_matches("*music*", "org.gnome.music"));
+        assert!(glob_matches("*music*", "music"));
+    }
+
+    #[test]
+    fn glob_question_mark_matches_one_character() {
+        assert_eq!(glob_to_regex("foo?"), "^foo.$");
+        assert!(glob_matches("foo?", "foot"));
+        assert!(!glob_matches("foo?", "foo"));
+    }
+
+    #[test]
+    fn glob_matches_whole_property() {
+        assert!(!glob_matches("music", "org.gnome.music"));
+    }
+
+    #[test]
+    fn glob_escapes_regex_characters() {
+        assert_eq!(glob_to_regex("a.b+(c)"), r"^a\.b\+\(c\)$");
+        assert!(glob_matches("a.b", "a.b"));
+        assert!(!glob_matches("a.b", "axb"));
+    }
+}
@@ -0,0 +1,92 @@
+use gtk::glib;
+
+// Fuzzy match the query against the text, returning the indices of the characters in the text that
+// matched. The characters of the query must appear in the text in order, but not necessarily next
+// to each other. Matching is case-insensitive.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<Vec<usize>> {
+    let mut indices = Vec::new();
+    let mut text_chars = text.chars().enumerate();
+
+    for query_char in query.chars() {
+        if query_char.is_whitespace() {
+            continue;
+        }
+
+        let (index, _) = text_chars
+            .by_ref()
+            .find(|(_, text_char)| text_char.to_lowercase().eq(query_char.to_lowercase()))?;
+
+        indices.push(index);
+    }
+
+    Some(indices)
+}
+
+// Return the text as Pango markup with the characters at the given indices emphasized.
+pub fn highlight(text: &str, indices: &[usize]) -> String {
+    let mut markup = String::with_capacity(text.len());
+
+    for (index, text_char) in text.chars().enumerate() {
+        let escaped = glib::markup_escape_text(text_char.encode_utf8(&mut [0; 4]));
+
+        if indices.contains(&index) {
+            markup.push_str("<b>");
+            markup.push_str(&escaped);
+            markup.push_str("</b>");
+        } else {
+            markup.push_str(&escaped);
+        }
+    }
+
+    markup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_match_characters_in_order() {
+        assert_eq!(fuzzy_match("ffx", "Firefox"), Some(vec![0, 4, 6]));
+    }
+
+    #[test]
+    fn fuzzy_match_characters_out_of_order() {
+        assert_eq!(fuzzy_match("xf", "Firefox"), None);
+    }
+
+    #[test]
+    fn fuzzy_match_ignores_case() {
+        assert_eq!(fuzzy_match("FIRE", "firefox"), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn fuzzy_match_ignores_whitespace_in_query() {
+        assert_eq!(fuzzy_match("fi re", "Firefox"), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn fuzzy_match_empty_query() {
+        assert_eq!(fuzzy_match("", "Firefox"), Some(Vec::new()));
+    }
+
+    #[test]
+    fn fuzzy_match_counts_characters_not_bytes() {
+        assert_eq!(fuzzy_match("ép", "Éléphant"), Some(vec![0, 3]));
+    }
+
+    #[test]
+    fn highlight_matched_characters() {
+        assert_eq!(highlight("Firefox", &[0, 4]), "<b>F</b>ire<b>f</b>ox");
+    }
+
+    #[test]
+    fn highlight_escapes_markup() {
+        assert_eq!(highlight("a<b>&c", &[1]), "a<b>&lt;</b>b&gt;&amp;c");
+    }
+
+    #[test]
+    fn highlight_nothing() {
+        assert_eq!(highlight("Firefox", &[]), "Firefox");
+    }
+}
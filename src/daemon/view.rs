@@ -0,0 +1,41 @@
+use serde::Deserialize;
+
+use super::diagnostic::ConfigError;
+use super::rules::Matcher;
+use swtchr::sway::{SortOrder, Window};
+
+// A window switcher with its own filter and layout, which can be opened with
+// `swtchr --view <name>`. Views share the same window history as the main window switcher.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ViewConfig {
+    // Only show the windows that match at least one of these. With no matchers, show every window.
+    #[serde(rename = "match")]
+    pub matchers: Vec<Matcher>,
+    // Only show the windows on the focused output.
+    pub current_output: bool,
+    // Override the top-level `group_by_app` and `sort` options for this view.
+    pub group_by_app: Option<bool>,
+    pub sort: Option<SortOrder>,
+}
+
+impl ViewConfig {
+    // Only keep the windows that belong in this view, keeping them in the same order.
+    pub fn filter_windows(
+        &self,
+        windows: Vec<Window>,
+        focused_output: Option<&str>,
+    ) -> Vec<Window> {
+        windows
+            .into_iter()
+            .filter(|window| {
+                self.matchers.is_empty() || self.matchers.iter().any(|m| m.matches(window))
+            })
+            .filter(|window| !self.current_output || window.output.as_deref() == focused_output)
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.matchers.iter().try_for_each(Matcher::validate)
+    }
+}
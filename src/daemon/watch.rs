@@ -0,0 +1,132 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+use eyre::{eyre, WrapErr};
+use inotify::{Inotify, WatchDescriptor, WatchMask};
+
+// The files in the config directory that swtchrd knows how to reload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigFile {
+    Config,
+    Css,
+}
+
+#[derive(Debug)]
+struct WatchedFile {
+    watch: WatchDescriptor,
+    name: OsString,
+    kind: ConfigFile,
+}
+
+fn watch_file(inotify: &mut Inotify, path: &Path, kind: ConfigFile) -> eyre::Result<WatchedFile> {
+    let dir = path.parent().ok_or(eyre!(
+        "The file path does not have a parent directory. This is a bug."
+    ))?;
+
+    let name = path
+        .file_name()
+        .ok_or(eyre!(
+            "The file path does not have a file name. This is a bug."
+        ))?
+        .to_owned();
+
+    // Many editors save files by writing a temporary file and renaming it over the original, so
+    // we need to watch the parent directory rather than the file itself.
+    let mask =
+        WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::MOVED_FROM | WatchMask::DELETE;
+
+    // Adding a watch for a directory that's already being watched returns the existing watch
+    // descriptor, so it's fine to call this once for each file.
+    let watch = inotify.add_watch(dir, mask).wrap_err_with(|| {
+        format!(
+            "Failed watching the directory {} for changes.",
+            dir.display()
+        )
+    })?;
+
+    Ok(WatchedFile { watch, name, kind })
+}
+
+// Watch the file, along with the file it points to if it's a symlink, like when it's managed by a
+// dotfile manager. Editing the file it points to doesn't change anything in the directory the
+// symlink is in. We only resolve the symlink once, so we don't follow it if it's changed to point
+// somewhere else.
+fn watch_file_and_target(
+    inotify: &mut Inotify,
+    path: &Path,
+    kind: ConfigFile,
+) -> eyre::Result<Vec<WatchedFile>> {
+    let mut watched_files = vec![watch_file(inotify, path, kind)?];
+
+    let is_symlink = fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_symlink());
+
+    // The symlink might be dangling until the user creates the file it points to. Watching the
+    // symlink still catches the file being replaced, so we don't fail here.
+    if is_symlink {
+        match fs::canonicalize(path) {
+            Ok(target) => watched_files.push(watch_file(inotify, &target, kind)?),
+            Err(err) => tracing::warn!(
+                path = %path.display(),
+                "Not watching the file this symlink points to for changes: {}",
+                err
+            ),
+        }
+    }
+
+    Ok(watched_files)
+}
+
+pub fn subscribe(
+    config_path: &Path,
+    css_path: &Path,
+) -> eyre::Result<async_channel::Receiver<eyre::Result<ConfigFile>>> {
+    let (sender, receiver) = async_channel::unbounded::<eyre::Result<ConfigFile>>();
+
+    let mut inotify = Inotify::init().wrap_err("Failed initializing inotify.")?;
+
+    let mut watched_files = watch_file_and_target(&mut inotify, config_path, ConfigFile::Config)?;
+
+    // The stylesheet is optional, and its directory might not exist if the user has overridden
+    // the path of the config file.
+    match watch_file_and_target(&mut inotify, css_path, ConfigFile::Css) {
+        Ok(watched) => watched_files.extend(watched),
+        Err(err) => tracing::warn!("Not watching the custom CSS file for changes: {:#}", err),
+    }
+
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+
+        'outer: loop {
+            let events = match inotify.read_events_blocking(&mut buf) {
+                Ok(events) => events,
+                Err(err) => {
+                    if sender.send_blocking(Err(err.into())).is_err() {
+                        break;
+                    }
+
+                    continue;
+                }
+            };
+
+            for event in events {
+                let changed = watched_files.iter().find(|file| {
+                    file.watch == event.wd && Some(file.name.as_os_str()) == event.name
+                });
+
+                if let Some(file) = changed {
+                    tracing::debug!(file = ?file.kind, mask = ?event.mask, "Config file changed.");
+
+                    if sender.send_blocking(Ok(file.kind)).is_err() {
+                        break 'outer;
+                    }
+                }
+            }
+        }
+
+        tracing::error!("Cannot send next config change: Channel unexpectedly closed.");
+    });
+
+    Ok(receiver)
+}
@@ -1,7 +1,7 @@
 use std::env;
 use std::path::PathBuf;
 
-use eyre::bail;
+use eyre::{bail, WrapErr};
 
 const SOCK_NAME: &str = "swtchrd.sock";
 
@@ -15,19 +15,30 @@ pub fn sock_path() -> PathBuf {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+// The prefix of the message for opening a named view. The name of the view follows it.
+const SHOW_VIEW_PREFIX: &[u8] = b"show-view:";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Command {
     Show,
+    ShowApp,
+    ShowWorkspaces,
+    ShowOutputs,
+    ShowView(String),
 }
 
 impl Command {
-    pub const BUF_LEN: usize = 16;
+    pub const BUF_LEN: usize = 256;
 
-    pub fn msg(&self) -> &[u8] {
+    pub fn msg(&self) -> Vec<u8> {
         use Command::*;
 
         match self {
-            Show => b"show",
+            Show => b"show".to_vec(),
+            ShowApp => b"show-app".to_vec(),
+            ShowWorkspaces => b"show-workspaces".to_vec(),
+            ShowOutputs => b"show-outputs".to_vec(),
+            ShowView(name) => [SHOW_VIEW_PREFIX, name.as_bytes()].concat(),
         }
     }
 
@@ -36,10 +47,19 @@ impl Command {
 
         Ok(match msg {
             b"show" => Show,
-            _ => bail!(
-                "Unrecognized command received over swtchrd IPC socket: '{:?}'.",
-                msg
-            ),
+            b"show-app" => ShowApp,
+            b"show-workspaces" => ShowWorkspaces,
+            b"show-outputs" => ShowOutputs,
+            _ => match msg.strip_prefix(SHOW_VIEW_PREFIX) {
+                Some(name) => ShowView(
+                    String::from_utf8(name.to_vec())
+                        .wrap_err("The view name received over swtchrd IPC socket is not UTF-8.")?,
+                ),
+                None => bail!(
+                    "Unrecognized command received over swtchrd IPC socket: '{:?}'.",
+                    msg
+                ),
+            },
         })
     }
 }
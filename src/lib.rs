@@ -5,5 +5,8 @@
 #[doc(hidden)]
 pub mod ipc;
 
+#[doc(hidden)]
+pub mod logging;
+
 #[doc(hidden)]
 pub mod sway;
@@ -0,0 +1,88 @@
+use std::env;
+use std::io;
+use std::os::fd::AsRawFd;
+
+use clap::{ArgAction, Args};
+use eyre::WrapErr;
+use nix::sys::stat::fstat;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::prelude::*;
+
+// The environment variable users can set to override the log filter. This accepts the same
+// directive syntax as `RUST_LOG`, e.g. `SWTCHR_LOG=swtchr::sway=trace`.
+const LOG_ENV_VAR: &str = "SWTCHR_LOG";
+
+#[derive(Args, Debug, Clone)]
+pub struct LogArgs {
+    /// Increase the logging verbosity. Pass more than once for more detail.
+    #[arg(short, long, action = ArgAction::Count, conflicts_with = "log_level")]
+    pub verbose: u8,
+
+    /// Set the log level (off, error, warn, info, debug, or trace).
+    #[arg(long, value_name = "LEVEL")]
+    pub log_level: Option<LevelFilter>,
+}
+
+impl LogArgs {
+    fn level(&self) -> LevelFilter {
+        if let Some(level) = self.log_level {
+            return level;
+        }
+
+        match self.verbose {
+            0 => LevelFilter::WARN,
+            1 => LevelFilter::INFO,
+            2 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        }
+    }
+}
+
+// Systemd sets this environment variable to the device and inode numbers of the journal stream
+// when a service's stdout/stderr is connected to the journal. When stderr is that stream, we log
+// to journald natively so that log levels and fields are preserved.
+//
+// Child processes inherit the environment variable even when their stderr goes somewhere else,
+// like when the user starts swtchr from a terminal in a session started by systemd, so we need to
+// check that stderr is actually the journal stream.
+fn is_journald_stream() -> bool {
+    let stream = env::var("JOURNAL_STREAM").unwrap_or_default();
+
+    let Some((dev, ino)) = stream.split_once(':') else {
+        return false;
+    };
+
+    match fstat(io::stderr().as_raw_fd()) {
+        Ok(stat) => dev.parse().ok() == Some(stat.st_dev) && ino.parse().ok() == Some(stat.st_ino),
+        Err(_) => false,
+    }
+}
+
+pub fn init(args: &LogArgs, identifier: &str) -> eyre::Result<()> {
+    let filter = EnvFilter::builder()
+        .with_default_directive(args.level().into())
+        .with_env_var(LOG_ENV_VAR)
+        .from_env_lossy();
+
+    let journald_layer = if is_journald_stream() {
+        // If we can't connect to journald for whatever reason, fall back to logging to stderr,
+        // which systemd will still capture.
+        tracing_journald::layer()
+            .ok()
+            .map(|layer| layer.with_syslog_identifier(identifier.to_owned()))
+    } else {
+        None
+    };
+
+    let stderr_layer = match journald_layer {
+        Some(_) => None,
+        None => Some(tracing_subscriber::fmt::layer().with_writer(io::stderr)),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(journald_layer)
+        .with(stderr_layer)
+        .try_init()
+        .wrap_err("Failed initializing the logger.")
+}
@@ -1,11 +1,13 @@
 use std::sync::{Mutex, OnceLock};
 
-use eyre::WrapErr;
+use eyre::{eyre, WrapErr};
+use gtk::gio::DesktopAppInfo;
+use gtk::prelude::*;
 use swayipc::Connection;
 
 use super::subscribe::SwayWindowId;
 
-fn connection() -> &'static Mutex<Connection> {
+pub(super) fn connection() -> &'static Mutex<Connection> {
     static CONNECTION: OnceLock<Mutex<Connection>> = OnceLock::new();
 
     CONNECTION.get_or_init(|| {
@@ -13,6 +15,20 @@ fn connection() -> &'static Mutex<Connection> {
     })
 }
 
+// Run a Sway command, failing with the given message if Sway couldn't run any part of it.
+pub(super) fn run_command(command: impl AsRef<str>, err_msg: &'static str) -> eyre::Result<()> {
+    tracing::debug!(command = %command.as_ref(), "Running Sway command.");
+
+    connection()
+        .lock()
+        .expect("Lock is poisoned.")
+        .run_command(command)
+        .wrap_err(err_msg)?
+        .into_iter()
+        .collect::<Result<(), _>>()
+        .wrap_err(err_msg)
+}
+
 pub fn switch_window(id: SwayWindowId) -> eyre::Result<()> {
     if id.is_null() {
         // The user attempted to select a window while the window switcher was empty. In this case,
@@ -20,14 +36,97 @@ pub fn switch_window(id: SwayWindowId) -> eyre::Result<()> {
         return Ok(());
     }
 
-    connection()
-        .lock()
-        .expect("Lock is poisoned.")
-        .run_command(format!("[con_id=\"{}\"] focus", id.0))
-        .wrap_err("Failed running Sway window switch command.")?
-        .into_iter()
-        .collect::<Result<_, _>>()
-        .wrap_err("Failed running Sway window switch command.")
+    run_command(
+        format!("[con_id=\"{}\"] focus", id.0),
+        "Failed running Sway window switch command.",
+    )
+}
+
+// Quote a string so that Sway treats it as a single argument.
+fn quote(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn switch_workspace(name: &str) -> eyre::Result<()> {
+    // Without `--no-auto-back-and-forth`, selecting the current workspace would switch to the
+    // previous one if the user has `workspace_auto_back_and_forth` enabled.
+    run_command(
+        format!("workspace --no-auto-back-and-forth {}", quote(name)),
+        "Failed running Sway workspace switch command.",
+    )
+}
+
+pub fn switch_output(name: &str) -> eyre::Result<()> {
+    run_command(
+        format!("focus output {}", quote(name)),
+        "Failed running Sway output focus command.",
+    )
+}
+
+// Remove the field codes like `%U` from the `Exec` line of a desktop entry, since we're not opening
+// any files with the app.
+//
+// https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html
+fn strip_field_codes(exec: &str) -> String {
+    let mut command = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+
+    while let Some(exec_char) = chars.next() {
+        if exec_char != '%' {
+            command.push(exec_char);
+            continue;
+        }
+
+        // A `%%` is a literal percent sign. Any other field code is dropped.
+        if chars.next() == Some('%') {
+            command.push('%');
+        }
+    }
+
+    command
+}
+
+// Launch the app via Sway rather than as a child of the daemon, so that the app isn't killed when
+// the daemon exits, like when its systemd service is restarted.
+pub fn launch_app(app_info: &DesktopAppInfo) -> eyre::Result<()> {
+    let exec = app_info
+        .commandline()
+        .ok_or(eyre!("This desktop entry does not have a command to run."))?;
+
+    let command = strip_field_codes(&exec.to_string_lossy());
+
+    tracing::debug!(desktop_file_id = ?app_info.id(), %command, "Launching app.");
+
+    // Quote the command so that Sway doesn't split it into multiple commands if it contains a `;`
+    // or `,`.
+    run_command(
+        format!("exec {}", quote(&command)),
+        "Failed running Sway command to launch an app.",
+    )
+}
+
+// Bring a window back from the scratchpad and focus it.
+pub fn show_scratchpad_window(id: SwayWindowId) -> eyre::Result<()> {
+    if id.is_null() {
+        return Ok(());
+    }
+
+    run_command(
+        format!("[con_id=\"{}\"] scratchpad show", id.0),
+        "Failed running Sway scratchpad show command.",
+    )
+}
+
+// Send a window back to the scratchpad.
+pub fn hide_scratchpad_window(id: SwayWindowId) -> eyre::Result<()> {
+    if id.is_null() {
+        return Ok(());
+    }
+
+    run_command(
+        format!("[con_id=\"{}\"] move scratchpad", id.0),
+        "Failed running Sway scratchpad move command.",
+    )
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -46,12 +145,18 @@ impl SwayMode {
 }
 
 pub fn switch_mode(mode: SwayMode) -> eyre::Result<()> {
+    run_command(
+        format!("mode {}", mode.name()),
+        "Failed running Sway binding mode switch command.",
+    )
+}
+
+pub fn send_tick(payload: &str) -> eyre::Result<()> {
     connection()
         .lock()
         .expect("Lock is poisoned.")
-        .run_command(format!("mode {}", mode.name()))
-        .wrap_err("Failed running Sway binding mode switch command.")?
-        .into_iter()
-        .collect::<Result<_, _>>()
-        .wrap_err("Failed running Sway binding mode switch command.")
+        .send_tick(payload)
+        .wrap_err("Failed sending a Sway tick event.")?;
+
+    Ok(())
 }
@@ -4,10 +4,39 @@ use gtk::gio::{self, DesktopAppInfo};
 use gtk::prelude::*;
 use swayipc::Node;
 
+use super::subscribe::Window;
+
 // The name of the standard icon used by Gnome when another icon could not be loaded.
 const GTK_MISSING_IMAGE_ICON: &str = "image-missing";
 
-#[derive(Debug, Clone)]
+// Search the installed desktop entries, returning them sorted by how well they match. Desktop
+// entries are only loaded as the iterator is consumed.
+pub fn search_desktop_entries(query: &str) -> impl Iterator<Item = DesktopAppInfo> {
+    DesktopAppInfo::search(query)
+        .into_iter()
+        .flatten()
+        .filter_map(|desktop_file_id| DesktopAppInfo::new(&desktop_file_id))
+}
+
+// Whether the window belongs to the app with this desktop entry. Desktop entries are usually named
+// after the app ID, and Xwayland apps can give their window class in the desktop entry.
+pub fn is_app_window(app_info: &DesktopAppInfo, window: &Window) -> bool {
+    let desktop_name = app_info
+        .id()
+        .map(|id| id.trim_end_matches(".desktop").to_owned());
+    let wm_class = app_info.startup_wm_class().map(String::from);
+
+    let matches_app_id = |name: &Option<String>| match (name, &window.app_id) {
+        (Some(name), Some(app_id)) => name.eq_ignore_ascii_case(app_id),
+        _ => false,
+    };
+
+    matches_app_id(&desktop_name)
+        || matches_app_id(&wm_class)
+        || (wm_class.is_some() && wm_class == window.class)
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct IconLocator {
     // Only Wayland windows have an app ID.
     app_id: Option<String>,
@@ -37,16 +66,10 @@ impl IconLocator {
 
     fn desktop_icon(&self) -> Option<gio::Icon> {
         for locator in self.locators() {
-            let options = DesktopAppInfo::search(locator);
-
-            for option_list in options {
-                for desktop_file_id in option_list {
-                    let maybe_icon =
-                        DesktopAppInfo::new(&desktop_file_id).and_then(|app_info| app_info.icon());
-
-                    if let Some(icon) = maybe_icon {
-                        return Some(icon);
-                    }
+            for app_info in search_desktop_entries(locator) {
+                if let Some(icon) = app_info.icon() {
+                    tracing::trace!(locator, desktop_file_id = ?app_info.id(), "Found window icon via desktop entry.");
+                    return Some(icon);
                 }
             }
         }
@@ -54,6 +77,27 @@ impl IconLocator {
         None
     }
 
+    // Return the strings, other than the window title, that the user might search for to find this
+    // window: the app ID, the X11 window class and instance, and the name of the app's desktop
+    // entry.
+    pub fn search_terms(&self) -> Vec<String> {
+        let desktop_name = self.locators().into_iter().find_map(|locator| {
+            search_desktop_entries(locator)
+                .next()
+                .map(|app_info| app_info.name().to_string())
+        });
+
+        [
+            self.app_id.clone(),
+            self.x_window_class.clone(),
+            self.x_window_instance.clone(),
+            desktop_name,
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
     pub fn icon(&self) -> eyre::Result<gtk::Image> {
         let display = gdk::Display::default().ok_or(eyre!("Could not connect to a display."))?;
         let theme = gtk::IconTheme::for_display(&display);
@@ -62,6 +106,7 @@ impl IconLocator {
         // user's theme if it exists.
         for locator in self.locators() {
             if theme.has_icon(locator) {
+                tracing::trace!(locator, "Found window icon in the icon theme.");
                 return Ok(gtk::Image::from_icon_name(locator));
             }
         }
@@ -72,7 +117,10 @@ impl IconLocator {
             .map(|icon| gtk::Image::from_gicon(&icon))
             // We weren't able to find an icon for the window, so fall back to the Gnome missing
             // image icon.
-            .unwrap_or_else(|| gtk::Image::from_icon_name(&String::from(GTK_MISSING_IMAGE_ICON))))
+            .unwrap_or_else(|| {
+                tracing::debug!(locators = ?self.locators(), "Could not find an icon for the window.");
+                gtk::Image::from_icon_name(&String::from(GTK_MISSING_IMAGE_ICON))
+            }))
     }
 }
 
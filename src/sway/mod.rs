@@ -1,10 +1,24 @@
 mod commands;
 mod icon;
+mod pin;
+mod preview;
 mod queue;
 mod session;
+mod slot;
 mod subscribe;
+mod tree;
 
-pub use commands::{switch_mode, switch_window, SwayMode};
-pub use icon::IconLocator;
+pub use commands::{
+    hide_scratchpad_window, launch_app, show_scratchpad_window, switch_mode, switch_output,
+    switch_window, switch_workspace, SwayMode,
+};
+pub use icon::{is_app_window, search_desktop_entries, IconLocator};
+pub use pin::{pin_window, toggle_pin, unpin_window};
+pub use preview::{clear_preview, preview_window, Preview};
+pub use queue::SortOrder;
 pub use session::check_is_sway_session;
+pub use slot::{go_to_slot, set_slot};
 pub use subscribe::{SwayWindowId, Window, WindowSubscription};
+pub use tree::{
+    focused_output, focused_window, focused_workspace, Output, SwayWorkspaceId, Workspace,
+};
@@ -0,0 +1,89 @@
+use swayipc::Node;
+
+use super::commands::run_command;
+use super::subscribe::SwayWindowId;
+use super::tree::get_tree;
+
+// Pinned windows have a mark with this prefix followed by a number, which puts the pinned windows
+// in the order they were pinned in. Storing pins as marks means they last as long as the windows
+// do, even if the daemon restarts. Sway doesn't show marks that start with an underscore in title
+// bars.
+const PIN_MARK_PREFIX: &str = "_swtchr_pin_";
+
+fn pin_mark(number: u64) -> String {
+    format!("{}{}", PIN_MARK_PREFIX, number)
+}
+
+// Return the number in the window's pin mark, if it's pinned.
+pub(super) fn pin_number(node: &Node) -> Option<u64> {
+    node.marks
+        .iter()
+        .find_map(|mark| mark.strip_prefix(PIN_MARK_PREFIX)?.parse().ok())
+}
+
+// Collect the pin number of every pinned window in this part of the tree.
+fn collect_pins(node: &Node, pins: &mut Vec<(SwayWindowId, u64)>) {
+    if let Some(number) = pin_number(node) {
+        pins.push((SwayWindowId(node.id), number));
+    }
+
+    for child in node.nodes.iter().chain(node.floating_nodes.iter()) {
+        collect_pins(child, pins);
+    }
+}
+
+fn get_pins() -> eyre::Result<Vec<(SwayWindowId, u64)>> {
+    let mut pins = Vec::new();
+    collect_pins(&get_tree()?, &mut pins);
+
+    Ok(pins)
+}
+
+// Pin the window after all the windows that are already pinned. This does nothing if the window
+// is already pinned.
+pub fn pin_window(id: SwayWindowId) -> eyre::Result<()> {
+    if id.is_null() {
+        return Ok(());
+    }
+
+    let pins = get_pins()?;
+
+    if pins.iter().any(|(pinned_id, _)| *pinned_id == id) {
+        return Ok(());
+    }
+
+    let next_number = pins.iter().map(|(_, number)| number + 1).max().unwrap_or(0);
+
+    run_command(
+        format!("[con_id=\"{}\"] mark --add {}", id.0, pin_mark(next_number)),
+        "Failed running Sway mark command.",
+    )
+}
+
+// Unpin the window. This does nothing if the window isn't pinned.
+pub fn unpin_window(id: SwayWindowId) -> eyre::Result<()> {
+    for (pinned_id, number) in get_pins()? {
+        if pinned_id == id {
+            run_command(
+                format!("unmark {}", pin_mark(number)),
+                "Failed running Sway mark command.",
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+// Pin the window if it isn't pinned, or unpin it if it is. Returns whether the window is pinned
+// now.
+pub fn toggle_pin(id: SwayWindowId) -> eyre::Result<bool> {
+    let is_pinned = get_pins()?.iter().any(|(pinned_id, _)| *pinned_id == id);
+
+    if is_pinned {
+        unpin_window(id)?;
+    } else {
+        pin_window(id)?;
+    }
+
+    Ok(!is_pinned)
+}
@@ -0,0 +1,112 @@
+use eyre::WrapErr;
+
+use super::commands::connection;
+use super::subscribe::SwayWindowId;
+use super::tree::get_window_ids;
+
+// The mark we put on the window being previewed. Sway shows marks in the title bar of a window
+// unless they start with an underscore, so this doubles as a visual indicator.
+const PREVIEW_MARK: &str = "swtchr_preview";
+
+// A window being highlighted in place without focusing it, by dimming every other window and
+// marking it.
+//
+// Sway doesn't tell us the opacity of each window, so we can't save it and put it back afterwards.
+// Instead, we dim windows relative to their current opacity and undo it the same way, which keeps
+// any opacity the user set with `for_window`.
+#[derive(Debug)]
+pub struct Preview {
+    dimmed: Vec<SwayWindowId>,
+    dim_by: f64,
+}
+
+impl Preview {
+    // The Sway commands to undo this preview, skipping any windows that have closed since.
+    fn undo_commands(&self, windows: &[SwayWindowId]) -> Vec<String> {
+        let mut commands = self
+            .dimmed
+            .iter()
+            .filter(|id| windows.contains(id))
+            .map(|id| format!("[con_id=\"{}\"] opacity plus {}", id.0, self.dim_by))
+            .collect::<Vec<_>>();
+
+        commands.push(format!("unmark {}", PREVIEW_MARK));
+
+        commands
+    }
+}
+
+// Run the commands, returning whether each one succeeded.
+fn run_preview_commands(commands: &[String]) -> eyre::Result<Vec<bool>> {
+    // A window can close between when we read the tree and when we run these commands, in which
+    // case the commands for that window fail, which is fine.
+    let command = commands.join("; ");
+
+    tracing::debug!(%command, "Running Sway window preview command.");
+
+    let results = connection()
+        .lock()
+        .expect("Lock is poisoned.")
+        .run_command(command)
+        .wrap_err("Failed running Sway window preview command.")?;
+
+    Ok(results.iter().map(Result::is_ok).collect())
+}
+
+// Preview the window, replacing the previous preview if there is one. This happens in a single
+// Sway command so that the other windows don't flicker.
+pub fn preview_window(
+    id: SwayWindowId,
+    opacity: f64,
+    previous: Option<Preview>,
+) -> eyre::Result<Option<Preview>> {
+    if id.is_null() {
+        return Ok(previous);
+    }
+
+    let windows = get_window_ids()?;
+    let others = windows
+        .iter()
+        .copied()
+        .filter(|window| *window != id)
+        .collect::<Vec<_>>();
+    let dim_by = 1.0 - opacity;
+
+    let mut commands = previous
+        .map(|previous| previous.undo_commands(&windows))
+        .unwrap_or_default();
+    let undo_count = commands.len();
+
+    commands.extend(
+        others
+            .iter()
+            .map(|window| format!("[con_id=\"{}\"] opacity minus {}", window.0, dim_by)),
+    );
+    commands.push(format!("[con_id=\"{}\"] mark --add {}", id.0, PREVIEW_MARK));
+
+    let succeeded = run_preview_commands(&commands)?;
+
+    // Sway refuses to dim a window below an opacity of 0, so a window the user already made
+    // translucent might not get dimmed. We only undim the windows we actually dimmed, so that we
+    // don't make the others more opaque than they were.
+    let dimmed = others
+        .into_iter()
+        .zip(succeeded.into_iter().skip(undo_count))
+        .filter_map(|(window, succeeded)| {
+            if !succeeded {
+                tracing::debug!(window = window.0, "Failed dimming window for preview.");
+            }
+
+            succeeded.then_some(window)
+        })
+        .collect();
+
+    Ok(Some(Preview { dimmed, dim_by }))
+}
+
+// Undo `preview_window`.
+pub fn clear_preview(preview: Preview) -> eyre::Result<()> {
+    run_preview_commands(&preview.undo_commands(&get_window_ids()?))?;
+
+    Ok(())
+}
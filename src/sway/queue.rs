@@ -1,41 +1,183 @@
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use swayipc::{WorkspaceChange, WorkspaceEvent};
 
 use super::subscribe::{SwayWindowId, Window, WindowEvent};
+use super::tree::{Output, SwayWorkspaceId, Workspace};
+
+// The orders the window switcher can show windows in. Other than `Recent`, these don't depend on
+// the window history, so windows stay in the same place each time the window switcher opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    // From most recently used to least recently used.
+    #[default]
+    Recent,
+
+    // By workspace, and then by position on the workspace, the way they appear in the tree.
+    Workspace,
+
+    // By app ID, and then by title.
+    Alphabetical,
+
+    // From oldest to newest.
+    Created,
+
+    // By a score that blends how often and how recently each window was used. Each time a window
+    // was used counts for less the longer ago it was.
+    Frecency,
+}
+
+// Sort windows that are in most-recently-used order into the given order. Every order other than
+// `Recent` and `Frecency` falls back to comparing window IDs, so the order never depends on the
+// window history. Those two orders come from the window history, so we leave the windows alone.
+pub fn sort_windows(windows: &mut [Window], order: SortOrder) {
+    match order {
+        SortOrder::Recent | SortOrder::Frecency => {}
+        SortOrder::Workspace => {
+            // Windows hidden in the scratchpad go last.
+            windows.sort_by_key(|window| (window.scratchpad, window.tree_position, window.id.0))
+        }
+        SortOrder::Alphabetical => windows.sort_by_cached_key(|window| {
+            (
+                window.app_id.as_deref().unwrap_or_default().to_lowercase(),
+                window.title.to_lowercase(),
+                window.id.0,
+            )
+        }),
+        // Sway assigns IDs in increasing order, so older windows have lower IDs.
+        SortOrder::Created => windows.sort_by_key(|window| window.id.0),
+    }
+}
+
+// How many times we remember each window being used. Uses older than this barely affect the
+// frecency score anyways.
+const MAX_HISTORY_LEN: usize = 100;
 
 #[derive(Debug, Clone)]
 struct WindowPriority {
     window: Window,
-    priority: u64,
+    // When the window was last moved to the front, either by being used or by being created or
+    // marked urgent.
+    raised: Instant,
+    // When the window was used, from oldest to newest. Creating a window or marking it urgent
+    // doesn't count as a use, so this can be empty.
+    history: VecDeque<Instant>,
+}
+
+impl WindowPriority {
+    fn last_used(&self) -> Option<Instant> {
+        self.history.back().copied()
+    }
+
+    // The window, along with when it was last moved to the front.
+    fn window(&self) -> Window {
+        Window {
+            last_used: Some(self.raised),
+            ..self.window.clone()
+        }
+    }
+
+    // Each use counts for 1 when it just happened and half as much for every `half_life` since.
+    fn frecency(&self, now: Instant, half_life: Duration) -> f64 {
+        self.history
+            .iter()
+            .map(|used| {
+                let age = now.saturating_duration_since(*used);
+                0.5f64.powf(age.as_secs_f64() / half_life.as_secs_f64())
+            })
+            .sum()
+    }
 }
 
 #[derive(Debug)]
 pub struct WindowQueue {
     map: HashMap<SwayWindowId, WindowPriority>,
-    // We're assuming this is big enough to never overflow.
-    highest_priority: u64,
+    // Whether focus events are being ignored.
+    frozen: bool,
+    // The most recently used window when the queue was frozen.
+    frozen_focus: Option<SwayWindowId>,
 }
 
 impl WindowQueue {
     pub fn new() -> Self {
         Self {
             map: HashMap::new(),
-            highest_priority: 0,
+            frozen: false,
+            frozen_focus: None,
+        }
+    }
+
+    // Move the window to the front without recording a use of it.
+    fn raise(&mut self, window: Window) -> &mut WindowPriority {
+        let now = Instant::now();
+
+        // Keep the history of the window, but update the window itself, since its title may have
+        // changed.
+        let window_priority = self
+            .map
+            .entry(window.id)
+            .and_modify(|window_priority| {
+                window_priority.window = window.clone();
+                window_priority.raised = now;
+            })
+            .or_insert_with(|| WindowPriority {
+                window,
+                raised: now,
+                history: VecDeque::new(),
+            });
+
+        window_priority
+    }
+
+    // Move the window to the front and record a use of it.
+    fn use_window(&mut self, window: Window) {
+        let window_priority = self.raise(window);
+
+        window_priority.history.push_back(window_priority.raised);
+
+        if window_priority.history.len() > MAX_HISTORY_LEN {
+            window_priority.history.pop_front();
         }
     }
 
     pub fn push_event(&mut self, event: WindowEvent) {
         match event {
             WindowEvent::Focus(window) => {
-                self.highest_priority += 1;
+                if !self.frozen {
+                    self.use_window(window);
+                }
+            }
+            WindowEvent::Raise(window) => {
+                self.raise(window);
+            }
+            WindowEvent::Freeze => {
+                self.frozen = true;
+                self.frozen_focus = self
+                    .map
+                    .values()
+                    .max_by_key(|window_priority| window_priority.last_used())
+                    .map(|window_priority| window_priority.window.id);
+            }
+            WindowEvent::Thaw(focused_id) => {
+                self.frozen = false;
 
-                self.map.insert(
-                    window.id,
-                    WindowPriority {
-                        window,
-                        priority: self.highest_priority,
-                    },
-                );
+                // Opening and dismissing the window switcher isn't another use of the window that
+                // was already focused.
+                if self.frozen_focus.take() == Some(focused_id) {
+                    return;
+                }
+
+                if let Some(window) = self
+                    .map
+                    .get(&focused_id)
+                    .map(|window_priority| window_priority.window.clone())
+                {
+                    self.use_window(window);
+                }
             }
             WindowEvent::Close(node_id) => {
                 self.map.remove(&node_id);
@@ -44,14 +186,336 @@ impl WindowQueue {
     }
 
     // Return the list of windows in the queue sorted from most recently used to least recently
-    // used.
+    // used. Windows that were created or marked urgent count as recently used here, so they're
+    // easy to switch to.
     pub fn sorted_windows(&self) -> Vec<Window> {
-        let mut list = self.map.values().cloned().collect::<Vec<_>>();
+        let mut list = self.map.values().collect::<Vec<_>>();
 
-        list.sort_by_key(|window_priority| cmp::Reverse(window_priority.priority));
+        list.sort_by_key(|window_priority| cmp::Reverse(window_priority.raised));
 
         list.into_iter()
-            .map(|window_priority| window_priority.window)
+            .map(WindowPriority::window)
             .collect::<Vec<_>>()
     }
+
+    // Return the list of windows in the queue sorted from highest to lowest frecency score. Windows
+    // with the same score are sorted from most recently used to least recently used.
+    pub fn frecent_windows(&self, half_life: Duration) -> Vec<Window> {
+        let now = Instant::now();
+
+        let mut list = self
+            .map
+            .values()
+            .map(|window_priority| (window_priority.frecency(now, half_life), window_priority))
+            .collect::<Vec<_>>();
+
+        list.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| b.raised.cmp(&a.raised))
+        });
+
+        list.into_iter()
+            .map(|(_, window_priority)| window_priority.window())
+            .collect::<Vec<_>>()
+    }
+}
+
+// Tracks the order in which workspaces and outputs were most recently focused. Focusing an output
+// focuses the workspace on it, so we can track both using workspace events.
+#[derive(Debug)]
+pub struct WorkspaceQueue {
+    map: HashMap<SwayWorkspaceId, u64>,
+    outputs: HashMap<String, u64>,
+    // We're assuming this is big enough to never overflow.
+    highest_priority: u64,
+    // Whether focus events are being ignored, like for the window queue.
+    frozen: bool,
+    // The workspace that was last focused while the queue was frozen, and its output.
+    frozen_focus: Option<(SwayWorkspaceId, Option<String>)>,
+}
+
+impl WorkspaceQueue {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            outputs: HashMap::new(),
+            highest_priority: 0,
+            frozen: false,
+            frozen_focus: None,
+        }
+    }
+
+    fn raise(&mut self, workspace_id: SwayWorkspaceId, output: Option<String>) {
+        self.highest_priority += 1;
+        self.map.insert(workspace_id, self.highest_priority);
+
+        if let Some(output) = output {
+            self.outputs.insert(output, self.highest_priority);
+        }
+    }
+
+    // Stop or start recording focus events. Peeking at workspaces and outputs focuses them, so we
+    // only record the workspace that ended up focused once the queue is unfrozen.
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+
+        if !frozen {
+            if let Some((workspace_id, output)) = self.frozen_focus.take() {
+                self.raise(workspace_id, output);
+            }
+        }
+    }
+
+    pub fn push_event(&mut self, event: &WorkspaceEvent) {
+        let Some(current) = &event.current else {
+            return;
+        };
+
+        match event.change {
+            WorkspaceChange::Focus => {
+                let workspace_id = SwayWorkspaceId(current.id);
+
+                if self.frozen {
+                    self.frozen_focus = Some((workspace_id, current.output.clone()));
+                } else {
+                    self.raise(workspace_id, current.output.clone());
+                }
+            }
+            WorkspaceChange::Empty => {
+                self.map.remove(&SwayWorkspaceId(current.id));
+            }
+            _ => {}
+        }
+    }
+
+    // Sort the workspaces from most recently used to least recently used. Workspaces that haven't
+    // been focused since the daemon started go last, in the order they were given.
+    pub fn sort_workspaces(&self, workspaces: &mut [Workspace]) {
+        workspaces.sort_by_key(|workspace| {
+            cmp::Reverse(self.map.get(&workspace.id).copied().unwrap_or(0))
+        });
+    }
+
+    // Sort the outputs from most recently used to least recently used, like `sort_workspaces`.
+    pub fn sort_outputs(&self, outputs: &mut [Output]) {
+        outputs.sort_by_key(|output| {
+            cmp::Reverse(self.outputs.get(&output.name).copied().unwrap_or(0))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sway::IconLocator;
+
+    fn window(id: i64, app_id: &str, title: &str) -> Window {
+        Window {
+            id: SwayWindowId(id),
+            title: title.to_owned(),
+            app_id: Some(app_id.to_owned()),
+            class: None,
+            instance: None,
+            workspace: None,
+            output: None,
+            floating: false,
+            scratchpad: false,
+            tree_position: 0,
+            pin: None,
+            last_used: None,
+            icon_locator: IconLocator::default(),
+        }
+    }
+
+    fn ids(windows: &[Window]) -> Vec<i64> {
+        windows.iter().map(|window| window.id.0).collect()
+    }
+
+    // A queue where each window was used the given numbers of seconds after the same instant, a
+    // minute ago.
+    fn queue_with_history(history: &[(i64, &[u64])]) -> WindowQueue {
+        let start = Instant::now() - Duration::from_secs(60);
+        let mut queue = WindowQueue::new();
+
+        for (id, uses) in history {
+            let history = uses
+                .iter()
+                .map(|secs| start + Duration::from_secs(*secs))
+                .collect::<VecDeque<_>>();
+
+            queue.map.insert(
+                SwayWindowId(*id),
+                WindowPriority {
+                    window: window(*id, "app", "title"),
+                    raised: history.back().copied().unwrap_or(start),
+                    history,
+                },
+            );
+        }
+
+        queue
+    }
+
+    fn use_count(queue: &WindowQueue, id: i64) -> usize {
+        queue.map[&SwayWindowId(id)].history.len()
+    }
+
+    #[test]
+    fn sort_recent_keeps_order() {
+        let mut windows = vec![window(2, "b", "b"), window(1, "a", "a")];
+        sort_windows(&mut windows, SortOrder::Recent);
+
+        assert_eq!(ids(&windows), vec![2, 1]);
+    }
+
+    #[test]
+    fn sort_alphabetical_by_app_id_then_title() {
+        let mut windows = vec![
+            window(1, "foot", "b"),
+            window(2, "Firefox", "z"),
+            window(3, "foot", "a"),
+        ];
+        sort_windows(&mut windows, SortOrder::Alphabetical);
+
+        assert_eq!(ids(&windows), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn sort_created_by_id() {
+        let mut windows = vec![
+            window(3, "a", "a"),
+            window(1, "b", "b"),
+            window(2, "c", "c"),
+        ];
+        sort_windows(&mut windows, SortOrder::Created);
+
+        assert_eq!(ids(&windows), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn sort_workspace_puts_scratchpad_last() {
+        let mut first = window(1, "a", "a");
+        first.scratchpad = true;
+        let mut second = window(2, "b", "b");
+        second.tree_position = 1;
+        let mut third = window(3, "c", "c");
+        third.tree_position = 0;
+
+        let mut windows = vec![first, second, third];
+        sort_windows(&mut windows, SortOrder::Workspace);
+
+        assert_eq!(ids(&windows), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn sorted_windows_most_recent_first() {
+        let queue = queue_with_history(&[(1, &[0, 3]), (2, &[2]), (3, &[1])]);
+
+        assert_eq!(ids(&queue.sorted_windows()), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn sorted_windows_have_last_used() {
+        let queue = queue_with_history(&[(1, &[0, 3])]);
+        let windows = queue.sorted_windows();
+
+        assert_eq!(
+            windows[0].last_used,
+            Some(queue.map[&SwayWindowId(1)].raised)
+        );
+    }
+
+    #[test]
+    fn frecency_halves_every_half_life() {
+        let queue = queue_with_history(&[(1, &[0])]);
+        let window_priority = &queue.map[&SwayWindowId(1)];
+        let half_life = Duration::from_secs(60);
+
+        let score = window_priority.frecency(window_priority.raised + half_life, half_life);
+
+        assert!((score - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn frecent_windows_favor_frequently_used() {
+        let queue = queue_with_history(&[(1, &[0, 1, 2]), (2, &[3]), (3, &[4])]);
+
+        assert_eq!(
+            ids(&queue.frecent_windows(Duration::from_secs(3600))),
+            vec![1, 3, 2]
+        );
+    }
+
+    #[test]
+    fn raise_moves_window_to_front_without_a_use() {
+        let mut queue = queue_with_history(&[(1, &[0, 1, 2]), (2, &[3])]);
+
+        queue.push_event(WindowEvent::Raise(window(1, "app", "title")));
+
+        assert_eq!(ids(&queue.sorted_windows()), vec![1, 2]);
+        assert_eq!(use_count(&queue, 1), 3);
+    }
+
+    #[test]
+    fn raise_adds_new_window_without_a_use() {
+        let mut queue = queue_with_history(&[(1, &[0]), (2, &[1])]);
+
+        queue.push_event(WindowEvent::Raise(window(3, "app", "title")));
+
+        assert_eq!(ids(&queue.sorted_windows()), vec![3, 2, 1]);
+        assert_eq!(use_count(&queue, 3), 0);
+    }
+
+    #[test]
+    fn raise_does_not_change_frecency() {
+        let mut queue = queue_with_history(&[(1, &[0]), (2, &[1])]);
+
+        queue.push_event(WindowEvent::Raise(window(1, "app", "title")));
+        queue.push_event(WindowEvent::Raise(window(3, "app", "title")));
+
+        assert_eq!(
+            ids(&queue.frecent_windows(Duration::from_secs(3600))),
+            vec![2, 1, 3]
+        );
+    }
+
+    #[test]
+    fn focus_is_ignored_while_frozen() {
+        let mut queue = queue_with_history(&[(1, &[0]), (2, &[1])]);
+
+        queue.push_event(WindowEvent::Freeze);
+        queue.push_event(WindowEvent::Focus(window(1, "app", "title")));
+
+        assert_eq!(use_count(&queue, 1), 1);
+    }
+
+    #[test]
+    fn thaw_on_same_window_is_not_a_use() {
+        let mut queue = queue_with_history(&[(1, &[0]), (2, &[1])]);
+
+        queue.push_event(WindowEvent::Freeze);
+        queue.push_event(WindowEvent::Thaw(SwayWindowId(2)));
+
+        assert_eq!(use_count(&queue, 2), 1);
+    }
+
+    #[test]
+    fn thaw_on_other_window_is_a_use() {
+        let mut queue = queue_with_history(&[(1, &[0]), (2, &[1])]);
+
+        queue.push_event(WindowEvent::Freeze);
+        queue.push_event(WindowEvent::Thaw(SwayWindowId(1)));
+
+        assert_eq!(use_count(&queue, 1), 2);
+    }
+
+    #[test]
+    fn close_removes_window() {
+        let mut queue = queue_with_history(&[(1, &[0]), (2, &[1])]);
+
+        queue.push_event(WindowEvent::Close(SwayWindowId(1)));
+
+        assert_eq!(ids(&queue.sorted_windows()), vec![2]);
+    }
 }
@@ -1,16 +1,38 @@
 use std::env;
 
-use eyre::bail;
+use eyre::{bail, WrapErr};
+use swayipc::Connection;
+
+// Check that we can actually talk to Sway over its IPC socket. This is the most reliable check we
+// have, because it doesn't depend on how the session was started. Display managers like greetd,
+// nested sessions, and distros like Regolith all set the session environment variables
+// differently.
+fn probe_sway_ipc() -> eyre::Result<String> {
+    match env::var_os("SWAYSOCK") {
+        Some(path) if !path.is_empty() => {}
+        _ => bail!("$SWAYSOCK is unset."),
+    }
+
+    let version = Connection::new()
+        .wrap_err("Failed connecting to the Sway IPC socket at $SWAYSOCK.")?
+        .get_version()
+        .wrap_err("The Sway IPC socket at $SWAYSOCK did not respond to a version request.")?;
+
+    Ok(version.human_readable)
+}
 
 // It's really important that we avoid false negatives here, so we should be as permissive as
 // possible in the values we accept for these environment variables. We should not fail if the
 // environment variables are unset.
-pub fn check_is_sway_session() -> eyre::Result<()> {
+fn check_session_env() -> eyre::Result<()> {
     if let Ok(session_type) = env::var("XDG_SESSION_TYPE") {
         let session_type_normalized = session_type.trim().to_lowercase();
 
         if !session_type_normalized.is_empty() && session_type_normalized != "wayland" {
-            bail!("You do not seem to be running in a Wayland session. This tool only supports the Sway window manager. You can run swtchrd with --no-check to override this.");
+            bail!(
+                "$XDG_SESSION_TYPE is `{}`, but expected `wayland`.",
+                session_type
+            );
         }
     }
 
@@ -27,10 +49,35 @@ pub fn check_is_sway_session() -> eyre::Result<()> {
                 && !session_normalized.starts_with("sway")
                 && !session_normalized.ends_with("sway")
             {
-                bail!("You do not seem to be running in a Sway session. This tool only supports the Sway window manager. You can run swtchrd with --no-check to override this.");
+                bail!("${} is `{}`, but expected `sway`.", session_var, session);
             }
         }
     }
 
     Ok(())
 }
+
+// We first try to reach Sway directly over IPC. If that fails, we fall back to checking the
+// session environment variables, and only fail if those tell us we're definitely not in a Sway
+// session. When we do fail, we report the result of both checks so the user can tell why.
+pub fn check_is_sway_session() -> eyre::Result<()> {
+    let probe_err = match probe_sway_ipc() {
+        Ok(version) => {
+            tracing::debug!(%version, "Connected to Sway over IPC.");
+            return Ok(());
+        }
+        Err(err) => err,
+    };
+
+    tracing::debug!("Could not connect to Sway over IPC: {:#}", probe_err);
+
+    if let Err(env_err) = check_session_env() {
+        bail!(
+            "You do not seem to be running in a Sway session. This tool only supports the Sway window manager. You can pass --no-check to override this.\n\nConnecting to Sway: {:#}\nChecking the session environment: {:#}",
+            probe_err,
+            env_err,
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use std::slice;
+
+use eyre::eyre;
+
+use super::commands::{run_command, show_scratchpad_window, switch_window};
+use super::subscribe::{SwayWindowId, Window};
+use super::tree::{get_tree, locate_windows};
+
+// Windows in a slot have a mark with this prefix followed by the slot number. Sway only lets one
+// window have each mark, so putting a window in a slot takes the slot away from any other window.
+const SLOT_MARK_PREFIX: &str = "_swtchr_slot_";
+
+fn slot_mark(slot: u32) -> String {
+    format!("{}{}", SLOT_MARK_PREFIX, slot)
+}
+
+// Put the window in the numbered slot, replacing the window that was there.
+pub fn set_slot(slot: u32, id: SwayWindowId) -> eyre::Result<()> {
+    run_command(
+        format!("[con_id=\"{}\"] mark --add {}", id.0, slot_mark(slot)),
+        "Failed running Sway mark command.",
+    )
+}
+
+// Switch to the window in the numbered slot, bringing it back from the scratchpad if it's hidden
+// there.
+pub fn go_to_slot(slot: u32) -> eyre::Result<()> {
+    let mark = slot_mark(slot);
+
+    let mut window = get_tree()?
+        .find_as_ref(|node| node.marks.contains(&mark))
+        .cloned()
+        .map(Window::from)
+        .ok_or(eyre!("There is no window in slot {}.", slot))?;
+
+    locate_windows(slice::from_mut(&mut window))?;
+
+    if window.scratchpad {
+        show_scratchpad_window(window.id)
+    } else {
+        switch_window(window.id)
+    }
+}
@@ -1,14 +1,26 @@
+use std::cell::Cell;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::sync_channel;
 use std::sync::{mpsc, Arc, RwLock};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use eyre::{bail, eyre, WrapErr};
 use gtk::glib;
 use swayipc::{self, Connection, Event, EventType, WindowChange};
 
-use super::queue::WindowQueue;
+use super::commands::send_tick;
+use super::queue::{sort_windows, SortOrder, WindowQueue, WorkspaceQueue};
+use super::tree::{focused_window, get_outputs, get_workspaces, locate_windows, Output, Workspace};
 use super::IconLocator;
 
+// The payloads of the Sway tick events we use to freeze and unfreeze the window queue. The thaw
+// payload is followed by the ID of the window that ended up focused.
+const FREEZE_TICK: &str = "swtchr:freeze";
+const THAW_TICK_PREFIX: &str = "swtchr:thaw:";
+
 fn filter_event(
     event_result: swayipc::Fallible<Event>,
     urgent_first: bool,
@@ -17,11 +29,32 @@ fn filter_event(
 
     let window_event = match event {
         Event::Window(window_event) => window_event,
+        Event::Tick(tick) => {
+            if tick.payload == FREEZE_TICK {
+                return Ok(Some(WindowEvent::Freeze));
+            }
+
+            return Ok(tick
+                .payload
+                .strip_prefix(THAW_TICK_PREFIX)
+                .and_then(|id| id.parse().ok())
+                .map(|id| WindowEvent::Thaw(SwayWindowId(id))));
+        }
         _ => return Ok(None),
     };
 
+    tracing::trace!(
+        change = ?window_event.change,
+        id = window_event.container.id,
+        app_id = ?window_event.container.app_id,
+        "Received Sway window event."
+    );
+
     match window_event.change {
-        WindowChange::New | WindowChange::Focus | WindowChange::Urgent => {
+        WindowChange::Focus => Ok(Some(WindowEvent::Focus(Window::from(
+            window_event.container,
+        )))),
+        WindowChange::New | WindowChange::Urgent => {
             if window_event.change == WindowChange::Urgent
                 && (!urgent_first || !window_event.container.urgent)
             {
@@ -32,7 +65,7 @@ fn filter_event(
                 return Ok(None);
             }
 
-            Ok(Some(WindowEvent::Focus(Window::from(
+            Ok(Some(WindowEvent::Raise(Window::from(
                 window_event.container,
             ))))
         }
@@ -46,11 +79,17 @@ fn filter_event(
 #[derive(Debug)]
 pub struct WindowSubscription {
     queue: Arc<RwLock<WindowQueue>>,
+    workspaces: Arc<RwLock<WorkspaceQueue>>,
     errors: mpsc::Receiver<eyre::Report>,
+    urgent_first: Arc<AtomicBool>,
+    frecency_half_life: Cell<Duration>,
 }
 
 impl WindowSubscription {
-    pub fn subscribe(urgent_first: bool) -> eyre::Result<WindowSubscription> {
+    pub fn subscribe(
+        urgent_first: bool,
+        frecency_half_life: Duration,
+    ) -> eyre::Result<WindowSubscription> {
         // We use a rendezvous channel because we don't want the errors piling up in an infinite
         // channel buffer until the next time the user opens the window switcher. The subscription
         // listener thread will block on the first error it encounters, and then that error will be
@@ -59,20 +98,56 @@ impl WindowSubscription {
 
         let connection = Connection::new().wrap_err("Failed acquiring a Sway IPC connection.")?;
         let subscription = connection
-            .subscribe([EventType::Window])
+            .subscribe([EventType::Window, EventType::Workspace, EventType::Tick])
             .wrap_err("Failed opening a Sway window event subscription.")?;
 
         let sending_queue = Arc::new(RwLock::new(WindowQueue::new()));
         let receiving_queue = Arc::clone(&sending_queue);
 
+        let sending_workspaces = Arc::new(RwLock::new(WorkspaceQueue::new()));
+        let receiving_workspaces = Arc::clone(&sending_workspaces);
+
+        let urgent_first = Arc::new(AtomicBool::new(urgent_first));
+        let thread_urgent_first = Arc::clone(&urgent_first);
+
         thread::spawn(move || {
             for event_result in subscription {
-                if let Some(result) = filter_event(event_result, urgent_first).transpose() {
+                if let Ok(Event::Workspace(workspace_event)) = &event_result {
+                    tracing::trace!(change = ?workspace_event.change, "Received Sway workspace event.");
+
+                    match sending_workspaces.write() {
+                        Ok(mut workspaces) => workspaces.push_event(workspace_event),
+                        Err(_) => {
+                            tracing::error!("Lock on workspace priority queue is poisoned.");
+                            break;
+                        }
+                    }
+
+                    continue;
+                }
+
+                if let Some(result) =
+                    filter_event(event_result, thread_urgent_first.load(Ordering::Relaxed))
+                        .transpose()
+                {
+                    // Peeking at workspaces and outputs shouldn't reorder them either.
+                    if let Ok(event @ (WindowEvent::Freeze | WindowEvent::Thaw(_))) = &result {
+                        match sending_workspaces.write() {
+                            Ok(mut workspaces) => {
+                                workspaces.set_frozen(matches!(event, WindowEvent::Freeze))
+                            }
+                            Err(_) => {
+                                tracing::error!("Lock on workspace priority queue is poisoned.");
+                                break;
+                            }
+                        }
+                    }
+
                     match result {
                         Ok(event) => match sending_queue.write() {
                             Ok(mut queue) => queue.push_event(event),
                             Err(_) => {
-                                eprintln!("Lock on window priority queue is poisoned.");
+                                tracing::error!("Lock on window priority queue is poisoned.");
                                 break;
                             }
                         },
@@ -80,7 +155,7 @@ impl WindowSubscription {
                             let is_closed = err_sender.send(err).is_err();
 
                             if is_closed {
-                                eprintln!(
+                                tracing::error!(
                                     "Cannot send Sway IPC error: Channel closed unexpectedly."
                                 );
                                 break;
@@ -93,31 +168,153 @@ impl WindowSubscription {
 
         Ok(Self {
             queue: receiving_queue,
+            workspaces: receiving_workspaces,
             errors: err_receiver,
+            urgent_first,
+            frecency_half_life: Cell::new(frecency_half_life),
         })
     }
 
-    pub fn get_window_list(&self) -> eyre::Result<Vec<Window>> {
-        // See if any errors have occurred since we last polled the window list.
+    // Change whether urgent windows are ordered first without resubscribing. This only affects
+    // events received from here on.
+    pub fn set_urgent_first(&self, urgent_first: bool) {
+        self.urgent_first.store(urgent_first, Ordering::Relaxed);
+    }
+
+    // Change how quickly window uses stop counting toward the frecency score.
+    pub fn set_frecency_half_life(&self, half_life: Duration) {
+        self.frecency_half_life.set(half_life);
+    }
+
+    // Stop window focus events from reordering the window queue, like while the user is peeking at
+    // windows in the window switcher. Windows can still be opened and closed while the queue is
+    // frozen.
+    //
+    // Rather than freezing the queue directly, we send a Sway tick event and freeze the queue when
+    // we receive it. Sway delivers events in order, so this way, focus events from before we froze
+    // the queue are still recorded even if they haven't reached us yet, and focus events from
+    // while it was frozen are ignored even if they reach us after we unfreeze it.
+    pub fn freeze(&self) -> eyre::Result<()> {
+        send_tick(FREEZE_TICK)
+            .wrap_err("Failed sending a Sway tick event to freeze the window queue.")
+    }
+
+    // Start recording window focus events again, treating the given window as the most recently
+    // focused one.
+    pub fn thaw(&self, focused: SwayWindowId) -> eyre::Result<()> {
+        send_tick(&format!("{}{}", THAW_TICK_PREFIX, focused.0))
+            .wrap_err("Failed sending a Sway tick event to unfreeze the window queue.")
+    }
+
+    // See if any errors have occurred since we last polled the window or workspace list.
+    fn check_errors(&self) -> eyre::Result<()> {
         match self.errors.try_recv() {
-            Ok(err) => return Err(err),
+            Ok(err) => Err(err),
             // Only fail when the channel is disconnected, not when the channel is empty.
             Err(mpsc::TryRecvError::Disconnected) => {
                 bail!("Cannot receive Sway IPC error: Channel closed unexpectedly.");
             }
-            _ => {}
+            _ => Ok(()),
         }
+    }
+
+    // Return the list of workspaces sorted from most recently used to least recently used.
+    pub fn get_workspace_list(&self) -> eyre::Result<Vec<Workspace>> {
+        self.check_errors()?;
 
-        match self.queue.read() {
-            Ok(queue) => Ok(queue.sorted_windows()),
-            Err(_) => Err(eyre!("Lock on window priority queue is poisoned.")),
+        let mut workspaces = get_workspaces()?;
+
+        match self.workspaces.read() {
+            Ok(queue) => {
+                queue.sort_workspaces(&mut workspaces);
+                tracing::debug!(count = workspaces.len(), "Polled the workspace list.");
+                Ok(workspaces)
+            }
+            Err(_) => Err(eyre!("Lock on workspace priority queue is poisoned.")),
         }
     }
+
+    // Return the list of active outputs sorted from most recently used to least recently used.
+    pub fn get_output_list(&self) -> eyre::Result<Vec<Output>> {
+        self.check_errors()?;
+
+        let mut outputs = get_outputs()?;
+
+        match self.workspaces.read() {
+            Ok(queue) => {
+                queue.sort_outputs(&mut outputs);
+                tracing::debug!(count = outputs.len(), "Polled the output list.");
+                Ok(outputs)
+            }
+            Err(_) => Err(eyre!("Lock on workspace priority queue is poisoned.")),
+        }
+    }
+
+    // Return the windows in the given order. Orders that don't depend on the window history start
+    // from most recently used to least recently used.
+    fn get_queued_windows(&self, order: SortOrder) -> eyre::Result<Vec<Window>> {
+        self.check_errors()?;
+
+        let mut windows = match self.queue.read() {
+            Ok(queue) if order == SortOrder::Frecency => {
+                queue.frecent_windows(self.frecency_half_life.get())
+            }
+            Ok(queue) => queue.sorted_windows(),
+            Err(_) => bail!("Lock on window priority queue is poisoned."),
+        };
+
+        locate_windows(&mut windows)?;
+        sort_windows(&mut windows, order);
+
+        Ok(windows)
+    }
+
+    pub fn get_window_list(&self, order: SortOrder) -> eyre::Result<Vec<Window>> {
+        let windows = self.get_queued_windows(order)?;
+
+        tracing::debug!(count = windows.len(), ?order, "Polled the window list.");
+
+        Ok(windows)
+    }
+
+    // Like `get_window_list`, but only return the windows that belong to the same app as the
+    // focused window.
+    pub fn get_app_window_list(&self, order: SortOrder) -> eyre::Result<Vec<Window>> {
+        // Ask Sway which window is focused rather than trusting the window history, which doesn't
+        // change while the window switcher is open.
+        let focused_id = focused_window().wrap_err("Failed getting the focused window.")?;
+
+        let mut windows = self.get_queued_windows(order)?;
+
+        let Some(focused) = focused_id
+            .and_then(|focused_id| windows.iter().find(|window| window.id == focused_id))
+            .cloned()
+        else {
+            return Ok(windows);
+        };
+
+        windows.retain(|window| {
+            window.id == focused.id || (focused.app_id.is_some() && window.app_id == focused.app_id)
+        });
+
+        tracing::debug!(app_id = ?focused.app_id, count = windows.len(), ?order, "Filtered the window list to the focused app.");
+
+        Ok(windows)
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, glib::ValueDelegate)]
 pub struct SwayWindowId(pub(super) i64);
 
+// So the user can pass a `con_id` on the command line.
+impl FromStr for SwayWindowId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
 impl SwayWindowId {
     pub fn is_null(&self) -> bool {
         self.0 == 0
@@ -125,9 +322,19 @@ impl SwayWindowId {
 }
 
 pub enum WindowEvent {
-    // A window was focused, created, or marked urgent.
+    // A window was focused.
     Focus(Window),
 
+    // A window was created or marked urgent, which moves it to the front like focusing it does,
+    // but doesn't count as a use of the window.
+    Raise(Window),
+
+    // The window queue should start ignoring focus events.
+    Freeze,
+
+    // The window queue should stop ignoring focus events, and this window ended up focused.
+    Thaw(SwayWindowId),
+
     // A window was closed.
     Close(SwayWindowId),
 }
@@ -136,14 +343,56 @@ pub enum WindowEvent {
 pub struct Window {
     pub id: SwayWindowId,
     pub title: String,
+    // The app ID for Wayland windows, or the window class for Xwayland windows. Windows with the
+    // same app ID belong to the same app.
+    pub app_id: Option<String>,
+    // Only Xwayland windows have these.
+    pub class: Option<String>,
+    pub instance: Option<String>,
+    // The names of the workspace and output the window is on, unless it's hidden in the
+    // scratchpad.
+    pub workspace: Option<String>,
+    pub output: Option<String>,
+    pub floating: bool,
+    // Whether the window is hidden in the scratchpad.
+    pub scratchpad: bool,
+    // Where the window is in the tree, counting windows depth-first.
+    pub(super) tree_position: usize,
+    // Pinned windows have a number, and they're shown first in order of their numbers.
+    pub pin: Option<u64>,
+    // When the window was last used or moved to the front by being created or marked urgent, if
+    // it's in the window history.
+    pub last_used: Option<Instant>,
     pub icon_locator: IconLocator,
 }
 
 impl From<swayipc::Node> for Window {
     fn from(node: swayipc::Node) -> Self {
+        let class = node
+            .window_properties
+            .as_ref()
+            .and_then(|props| props.class.clone());
+        let instance = node
+            .window_properties
+            .as_ref()
+            .and_then(|props| props.instance.clone());
+        let app_id = node.app_id.clone().or_else(|| class.clone());
+
+        // Window events don't tell us which workspace the window is on, so these get filled in
+        // from the tree when we need them.
         Self {
             id: SwayWindowId(node.id),
             title: node.name.as_ref().cloned().unwrap_or_else(String::new),
+            app_id,
+            class,
+            instance,
+            workspace: None,
+            output: None,
+            floating: node.node_type == swayipc::NodeType::FloatingCon,
+            scratchpad: false,
+            tree_position: 0,
+            pin: None,
+            last_used: None,
             icon_locator: node.into(),
         }
     }
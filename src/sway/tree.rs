@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use eyre::WrapErr;
+use swayipc::{Node, NodeType};
+
+use super::commands::connection;
+use super::pin::pin_number;
+use super::subscribe::{SwayWindowId, Window};
+
+// The name of the hidden workspace Sway keeps scratchpad windows on.
+const SCRATCHPAD_WORKSPACE: &str = "__i3_scratch";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SwayWorkspaceId(pub(super) i64);
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub id: SwayWorkspaceId,
+    pub name: String,
+    pub output: Option<String>,
+    // The windows on this workspace, in tree order.
+    pub windows: Vec<Window>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub name: String,
+    pub make: String,
+    pub model: String,
+    // The name of the workspace that's visible on this output.
+    pub current_workspace: Option<String>,
+}
+
+impl From<swayipc::Output> for Output {
+    fn from(output: swayipc::Output) -> Self {
+        Self {
+            name: output.name,
+            make: output.make,
+            model: output.model,
+            current_workspace: output.current_workspace,
+        }
+    }
+}
+
+// Collect the windows in this part of the tree, in tree order.
+fn collect_windows(node: &Node, windows: &mut Vec<Window>) {
+    let is_window = matches!(node.node_type, NodeType::Con | NodeType::FloatingCon)
+        && node.nodes.is_empty()
+        && node.floating_nodes.is_empty();
+
+    if is_window {
+        windows.push(Window::from(node.clone()));
+    }
+
+    for child in node.nodes.iter().chain(node.floating_nodes.iter()) {
+        collect_windows(child, windows);
+    }
+}
+
+pub(super) fn get_tree() -> eyre::Result<Node> {
+    connection()
+        .lock()
+        .expect("Lock is poisoned.")
+        .get_tree()
+        .wrap_err("Failed getting the Sway tree.")
+}
+
+// Return the ID of every window, including the ones in the scratchpad.
+pub(super) fn get_window_ids() -> eyre::Result<Vec<SwayWindowId>> {
+    let mut windows = Vec::new();
+    collect_windows(&get_tree()?, &mut windows);
+
+    Ok(windows.into_iter().map(|window| window.id).collect())
+}
+
+fn collect_workspaces(node: &Node, workspaces: &mut Vec<Workspace>) {
+    if node.node_type == NodeType::Workspace {
+        let name = node.name.clone().unwrap_or_default();
+
+        if name == SCRATCHPAD_WORKSPACE {
+            return;
+        }
+
+        let mut windows = Vec::new();
+        collect_windows(node, &mut windows);
+
+        workspaces.push(Workspace {
+            id: SwayWorkspaceId(node.id),
+            name,
+            output: node.output.clone(),
+            windows,
+        });
+
+        return;
+    }
+
+    for child in node.nodes.iter() {
+        collect_workspaces(child, workspaces);
+    }
+}
+
+// Return every workspace in tree order, not including the scratchpad.
+pub fn get_workspaces() -> eyre::Result<Vec<Workspace>> {
+    let mut workspaces = Vec::new();
+    collect_workspaces(&get_tree()?, &mut workspaces);
+
+    Ok(workspaces)
+}
+
+// Where a window is in the tree.
+#[derive(Debug)]
+struct WindowLocation {
+    workspace: String,
+    output: Option<String>,
+    floating: bool,
+    tree_position: usize,
+    pin: Option<u64>,
+}
+
+fn collect_window_locations<'a>(
+    node: &'a Node,
+    mut output: Option<&'a str>,
+    mut workspace: Option<&'a str>,
+    locations: &mut HashMap<SwayWindowId, WindowLocation>,
+) {
+    match node.node_type {
+        NodeType::Output => output = node.name.as_deref(),
+        NodeType::Workspace => workspace = node.name.as_deref(),
+        _ => {}
+    }
+
+    let is_window = matches!(node.node_type, NodeType::Con | NodeType::FloatingCon)
+        && node.nodes.is_empty()
+        && node.floating_nodes.is_empty();
+
+    if let (true, Some(workspace)) = (is_window, workspace) {
+        locations.insert(
+            SwayWindowId(node.id),
+            WindowLocation {
+                workspace: workspace.to_owned(),
+                output: output.map(String::from),
+                floating: node.node_type == NodeType::FloatingCon,
+                tree_position: locations.len(),
+                pin: pin_number(node),
+            },
+        );
+    }
+
+    for child in node.nodes.iter().chain(node.floating_nodes.iter()) {
+        collect_window_locations(child, output, workspace, locations);
+    }
+}
+
+// Fill in which workspace and output each window is on, whether it's floating, whether it's hidden
+// in the scratchpad, and whether it's pinned. Window events don't tell us any of this, so we need
+// to check the tree.
+pub fn locate_windows(windows: &mut [Window]) -> eyre::Result<()> {
+    let mut locations = HashMap::new();
+    collect_window_locations(&get_tree()?, None, None, &mut locations);
+
+    for window in windows.iter_mut() {
+        if let Some(location) = locations.remove(&window.id) {
+            window.scratchpad = location.workspace == SCRATCHPAD_WORKSPACE;
+            window.floating = location.floating;
+            window.tree_position = location.tree_position;
+            window.pin = location.pin;
+            window.workspace = (!window.scratchpad).then_some(location.workspace);
+            window.output = location.output.filter(|_| !window.scratchpad);
+        }
+    }
+
+    Ok(())
+}
+
+// Return every active output.
+pub fn get_outputs() -> eyre::Result<Vec<Output>> {
+    let outputs = connection()
+        .lock()
+        .expect("Lock is poisoned.")
+        .get_outputs()
+        .wrap_err("Failed getting the list of Sway outputs.")?;
+
+    Ok(outputs
+        .into_iter()
+        .filter(|output| output.active)
+        .map(Output::from)
+        .collect())
+}
+
+// Return the name of the focused output.
+pub fn focused_output() -> eyre::Result<Option<String>> {
+    let outputs = connection()
+        .lock()
+        .expect("Lock is poisoned.")
+        .get_outputs()
+        .wrap_err("Failed getting the list of Sway outputs.")?;
+
+    Ok(outputs
+        .into_iter()
+        .find(|output| output.focused)
+        .map(|output| output.name))
+}
+
+// Return the name of the focused workspace.
+pub fn focused_workspace() -> eyre::Result<Option<String>> {
+    let workspaces = connection()
+        .lock()
+        .expect("Lock is poisoned.")
+        .get_workspaces()
+        .wrap_err("Failed getting the list of Sway workspaces.")?;
+
+    Ok(workspaces
+        .into_iter()
+        .find(|workspace| workspace.focused)
+        .map(|workspace| workspace.name))
+}
+
+// Return the ID of the focused window, if a window is focused.
+pub fn focused_window() -> eyre::Result<Option<SwayWindowId>> {
+    Ok(get_tree()?
+        .find_focused_as_ref(|node| {
+            node.focused && matches!(node.node_type, NodeType::Con | NodeType::FloatingCon)
+        })
+        .map(|node| SwayWindowId(node.id)))
+}